@@ -1,5 +1,5 @@
 use criterion::{Criterion, criterion_group, criterion_main};
-use fib_rs::Fib;
+use fib_rs::{Algorithm, Fib};
 use std::hint::black_box;
 
 fn fib_n_benchmark(c: &mut Criterion) {
@@ -19,6 +19,49 @@ fn fib_n_benchmark(c: &mut Criterion) {
     });
 }
 
+fn fib_doubling_benchmark(c: &mut Criterion) {
+    // One thread isolates the doubling loop and its buffers from parallel multiplication
+    let pool = rayon::ThreadPoolBuilder::new()
+        .num_threads(1)
+        .build()
+        .unwrap();
+    let mut g = c.benchmark_group("Sequential F(n) Benchmarks");
+    g.sample_size(10);
+
+    for (name, n) in [
+        ("n = 1,000,000", 1_000_000),
+        ("n = 4,000,000", 4_000_000),
+        ("n = 10,000,000", 10_000_000),
+    ] {
+        g.bench_function(name, |b| {
+            b.iter(|| pool.install(|| Fib::single(black_box(n))));
+        });
+    }
+}
+
+fn fib_small_benchmark(c: &mut Criterion) {
+    let mut g = c.benchmark_group("Small F(n) Benchmarks");
+
+    g.bench_function("single_u64 n = 90", |b| {
+        b.iter(|| Fib::single_u64(black_box(90)));
+    });
+    g.bench_function("single n = 90", |b| b.iter(|| Fib::single(black_box(90))));
+    g.bench_function("range 0-186", |b| {
+        b.iter(|| Fib::range(black_box(0), black_box(186)));
+    });
+}
+
+fn fib_algorithm_benchmark(c: &mut Criterion) {
+    let mut g = c.benchmark_group("Algorithm Benchmarks (n = 1,000,000)");
+    g.sample_size(10);
+
+    for algorithm in [Algorithm::Matrix, Algorithm::FastDoubling, Algorithm::Lucas] {
+        g.bench_function(algorithm.name(), |b| {
+            b.iter(|| algorithm.single(black_box(1_000_000)));
+        });
+    }
+}
+
 fn fib_sequence_benchmark(c: &mut Criterion) {
     let mut g = c.benchmark_group("Fibonacci Sequence Benchmarks (Inclusive)");
     g.sample_size(10);
@@ -43,5 +86,12 @@ fn fib_sequence_benchmark(c: &mut Criterion) {
     });
 }
 
-criterion_group!(benches, fib_n_benchmark, fib_sequence_benchmark);
+criterion_group!(
+    benches,
+    fib_n_benchmark,
+    fib_doubling_benchmark,
+    fib_small_benchmark,
+    fib_algorithm_benchmark,
+    fib_sequence_benchmark
+);
 criterion_main!(benches);
@@ -0,0 +1,276 @@
+//! Alternative algorithms for computing a single Fibonacci number.
+//!
+//! All algorithms return identical values, so they can be used to cross-check each other or
+//! to compare their performance:
+//!
+//! - **Naive**: n additions of ever larger numbers, O(n²) bit operations
+//! - **Matrix**: binary powering of the symmetric matrix [[F(k+1), F(k)], [F(k), F(k-1)]]
+//! - **Fast doubling**: the default, F(2k±1) from the squares of F(k) and F(k-1)
+//! - **Lucas**: doubling of the pair (F(k), L(k)) with F(2k) = F(k)L(k), L(2k) = L(k)² ∓ 2
+
+use std::{convert::Infallible, error::Error, fmt, str::FromStr};
+
+use num_bigint::BigUint;
+use num_traits::{One, Zero};
+
+use crate::{Control, Fib, FibError};
+
+/// Number of additions performed by the naive algorithm between progress reports.
+const NAIVE_STEP_LEN: u128 = 1024;
+
+/// The algorithm used to compute a single Fibonacci number.
+///
+/// # Examples
+///
+/// ```
+/// use fib_rs::{Algorithm, Fib};
+///
+/// for algorithm in Algorithm::ALL {
+///     assert_eq!(algorithm.single(300), Fib::single(300));
+/// }
+/// assert_eq!("matrix".parse::<Algorithm>(), Ok(Algorithm::Matrix));
+/// assert_eq!(Algorithm::FastDoubling.to_string(), "fast-doubling");
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+#[non_exhaustive]
+pub enum Algorithm {
+    /// Iterated addition F(k+2) = F(k+1) + F(k)
+    Naive,
+    /// Binary powering of the 2×2 matrix [[1, 1], [1, 0]]
+    Matrix,
+    /// Fast doubling of (F(k), F(k-1)) with two squarings per step
+    #[default]
+    FastDoubling,
+    /// Doubling of Fibonacci and Lucas numbers with F(2k) = F(k)L(k) and L(2k) = L(k)^2 - 2(-1)^k
+    Lucas,
+}
+
+impl Algorithm {
+    /// Every available algorithm.
+    pub const ALL: [Self; 4] = [Self::Naive, Self::Matrix, Self::FastDoubling, Self::Lucas];
+
+    /// The name used by `Display` and `FromStr`.
+    #[must_use]
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Naive => "naive",
+            Self::Matrix => "matrix",
+            Self::FastDoubling => "fast-doubling",
+            Self::Lucas => "lucas",
+        }
+    }
+
+    /// Calculate the nth Fibonacci number with this algorithm.
+    ///
+    /// # Arguments
+    ///
+    /// * `n` - The index of the Fibonacci number to calculate
+    ///
+    /// # Returns
+    ///
+    /// * The nth Fibonacci number as a `BigUint`
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use fib_rs::Algorithm;
+    /// use num_bigint::BigUint;
+    ///
+    /// assert_eq!(Algorithm::Lucas.single(10), BigUint::from(55u32));
+    /// ```
+    #[must_use]
+    pub fn single(self, n: u128) -> BigUint {
+        let Ok(value) = self.compute(n, &mut || Ok::<_, Infallible>(()));
+        value
+    }
+
+    /// Calculate the nth Fibonacci number with this algorithm, observing `control`.
+    ///
+    /// Progress counts doubling steps, or blocks of additions for the naive algorithm.
+    pub(crate) fn single_with(self, n: u128, control: &Control) -> Result<BigUint, FibError> {
+        control.check()?;
+        let total = self.steps(n);
+        let mut completed = 0;
+        self.compute(n, &mut || {
+            completed += 1;
+            control.report(completed, total);
+            control.check()
+        })
+    }
+
+    /// Number of times `compute` calls its step hook for index `n`.
+    fn steps(self, n: u128) -> u64 {
+        let steps = match self {
+            Self::Naive => n / NAIVE_STEP_LEN,
+            Self::Matrix | Self::FastDoubling | Self::Lucas => {
+                u128::from(u128::BITS - n.leading_zeros())
+            }
+        };
+        u64::try_from(steps).unwrap_or(u64::MAX)
+    }
+
+    /// Compute F(n), calling `step` after every unit of work.
+    fn compute<E, F>(self, n: u128, step: &mut F) -> Result<BigUint, E>
+    where
+        F: FnMut() -> Result<(), E>,
+    {
+        match self {
+            Self::Naive => naive(n, step),
+            Self::Matrix => matrix_power(n, step),
+            Self::FastDoubling => Fib::fib_single_steps(n, step),
+            Self::Lucas => lucas_doubling(n, step),
+        }
+    }
+}
+
+impl fmt::Display for Algorithm {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FromStr for Algorithm {
+    type Err = ParseAlgorithmError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .into_iter()
+            .find(|algorithm| algorithm.name() == s)
+            .ok_or_else(|| ParseAlgorithmError(s.to_owned()))
+    }
+}
+
+/// Error returned when parsing an unknown algorithm name.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseAlgorithmError(String);
+
+impl fmt::Display for ParseAlgorithmError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let names: Vec<_> = Algorithm::ALL.iter().map(|a| a.name()).collect();
+        write!(
+            f,
+            "unknown algorithm `{}`, expected one of: {}",
+            self.0,
+            names.join(", ")
+        )
+    }
+}
+
+impl Error for ParseAlgorithmError {}
+
+/// Compute F(n) by iterated addition.
+fn naive<E, F>(n: u128, step: &mut F) -> Result<BigUint, E>
+where
+    F: FnMut() -> Result<(), E>,
+{
+    let (mut a, mut b) = (BigUint::zero(), BigUint::one());
+    for i in 1..=n {
+        b += &a;
+        std::mem::swap(&mut a, &mut b);
+        if i % NAIVE_STEP_LEN == 0 {
+            step()?;
+        }
+    }
+    Ok(a)
+}
+
+/// Compute F(n) as an entry of [[1, 1], [1, 0]]^n by left-to-right binary powering.
+///
+/// Powers of the matrix are symmetric, so only (F(k+1), F(k), F(k-1)) is kept.
+fn matrix_power<E, F>(n: u128, step: &mut F) -> Result<BigUint, E>
+where
+    F: FnMut() -> Result<(), E>,
+{
+    // The identity matrix, k = 0
+    let (mut a, mut b, mut c) = (BigUint::one(), BigUint::zero(), BigUint::one());
+    for bit in (0..u128::BITS - n.leading_zeros()).rev() {
+        // [[a, b], [b, c]]^2 = [[a² + b², b(a + c)], [b(a + c), b² + c²]]
+        let b2 = &b * &b;
+        let off = &b * (&a + &c);
+        a = &a * &a + &b2;
+        c = &c * &c + b2;
+        b = off;
+        if n >> bit & 1 == 1 {
+            // Multiply by [[1, 1], [1, 0]]
+            let sum = &a + &b;
+            c = std::mem::replace(&mut b, std::mem::replace(&mut a, sum));
+        }
+        step()?;
+    }
+    Ok(b)
+}
+
+/// Compute F(n) by doubling the pair (F(k), L(k)) from the most significant bit down.
+fn lucas_doubling<E, F>(n: u128, step: &mut F) -> Result<BigUint, E>
+where
+    F: FnMut() -> Result<(), E>,
+{
+    // (F(0), L(0)) = (0, 2)
+    let (mut f, mut l) = (BigUint::zero(), BigUint::from(2u32));
+    let mut k_is_odd = false;
+    for bit in (0..u128::BITS - n.leading_zeros()).rev() {
+        // F(2k) = F(k)L(k) and L(2k) = L(k)² - 2(-1)^k
+        f *= &l;
+        l = &l * &l;
+        if k_is_odd {
+            l += 2u32;
+        } else {
+            l -= 2u32;
+        }
+        k_is_odd = false;
+        if n >> bit & 1 == 1 {
+            // F(k+1) = (F(k) + L(k)) / 2 and L(k+1) = (5F(k) + L(k)) / 2
+            let next_f = (&f + &l) >> 1;
+            l = (&f * 5u32 + &l) >> 1;
+            f = next_f;
+            k_is_odd = true;
+        }
+        step()?;
+    }
+    Ok(f)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn algorithms_agree() {
+        for n in (0..300).chain([1_000, 1_023, 1_024, 4_097]) {
+            let expected = Fib::single(n);
+            for algorithm in Algorithm::ALL {
+                assert_eq!(algorithm.single(n), expected, "{algorithm} F({n})");
+            }
+        }
+        for algorithm in [Algorithm::Matrix, Algorithm::Lucas] {
+            assert_eq!(algorithm.single(123_457), Fib::single(123_457));
+        }
+    }
+
+    #[test]
+    fn names_round_trip() {
+        for algorithm in Algorithm::ALL {
+            assert_eq!(algorithm.to_string().parse(), Ok(algorithm));
+        }
+        let error = "bogus".parse::<Algorithm>().unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "unknown algorithm `bogus`, expected one of: naive, matrix, fast-doubling, lucas"
+        );
+    }
+
+    #[test]
+    fn step_counts() {
+        for algorithm in Algorithm::ALL {
+            let mut calls = 0;
+            let value = algorithm
+                .compute(5_000, &mut || {
+                    calls += 1;
+                    Ok::<_, Infallible>(())
+                })
+                .unwrap();
+            assert_eq!(value, Fib::single(5_000));
+            assert_eq!(calls, algorithm.steps(5_000), "{algorithm}");
+        }
+    }
+}
@@ -0,0 +1,510 @@
+//! Size and leading digits of F(n) without computing it.
+//!
+//! For n ≥ 2 the Fibonacci numbers satisfy F(n) = φ^n / √5 * (1 - (-1)^n * φ^(-2n)), so
+//!
+//! - log10 F(n) = n * log10(φ) - log10(√5) + δ with |δ| < φ^(-2n)
+//!
+//! The logarithms are evaluated in binary fixed point with a rigorous error bound. The
+//! precision is raised until the bound no longer straddles a digit boundary, and small
+//! indices, where δ matters at the working precision, are computed exactly.
+//!
+//! Exact bit and digit counts use the same enclosure, doubling the precision while it
+//! straddles a power of the base, up to [`MAX_LEN_PRECISION`] bits. F(n) is never computed for
+//! indices above that cap.
+//!
+//! The enclosure straddles B^m only while the distance of (n * log(φ) - log(√5)) / log(B) from
+//! the integer m is below its width. This is nonzero, since F(n) is a power of 2 or 10 only for
+//! n ≤ 6. As log(φ) / log(B) is irrational, the values are equidistributed modulo 1, so an index
+//! comes within 2^-k of an integer with probability about 2^-k, and one of the 2^128 indices
+//! needs more than 2^16 bits with probability about 2^-65000. Matveev's lower bound for linear
+//! forms in logarithms excludes it rigorously only down to about 2^-(5 * 10^14), far beyond any
+//! feasible precision. A length that has not settled at the cap is reported by a panic rather
+//! than by computing F(n).
+
+use std::fmt;
+
+use num_bigint::BigUint;
+use num_traits::{One, ToPrimitive, Zero};
+
+use crate::Fib;
+
+/// Indices up to this bound are always computed exactly.
+const EXACT_INDEX_BOUND: u32 = 1_000;
+
+/// Extra fractional bits carried beyond the estimated requirement.
+const GUARD_BITS: u64 = 64;
+
+/// Most fractional bits used to settle an exact bit or digit count.
+const MAX_LEN_PRECISION: u64 = 1 << 16;
+
+/// Number of halvings applied to the argument of the exponential series.
+const EXP_REDUCTION_STEPS: u32 = 8;
+
+/// The decimal size and leading digits of a Fibonacci number.
+///
+/// F(n) lies in [leading * 10^(d-k), (leading + 1) * 10^(d-k)), where d is the digit count and
+/// k the number of leading digits.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FibApprox {
+    digit_count: BigUint,
+    leading_digits: BigUint,
+}
+
+impl FibApprox {
+    /// The number of decimal digits of F(n).
+    #[must_use]
+    pub fn digit_count(&self) -> &BigUint {
+        &self.digit_count
+    }
+
+    /// The leading digits of F(n), truncated (not rounded).
+    ///
+    /// Holds fewer digits than requested only when F(n) itself is that short.
+    #[must_use]
+    pub fn leading_digits(&self) -> &BigUint {
+        &self.leading_digits
+    }
+
+    /// The decimal exponent of F(n) in scientific notation, i.e. the digit count minus one.
+    #[must_use]
+    pub fn exponent(&self) -> BigUint {
+        &self.digit_count - 1u32
+    }
+}
+
+impl fmt::Display for FibApprox {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let leading = self.leading_digits.to_string();
+        let (first, rest) = leading.split_at(1);
+        if rest.is_empty() {
+            write!(f, "{first}e{}", self.exponent())
+        } else {
+            write!(f, "{first}.{rest}e{}", self.exponent())
+        }
+    }
+}
+
+impl Fib {
+    /// Approximate the nth Fibonacci number in scientific notation.
+    ///
+    /// Returns the exact decimal digit count of F(n) and its first `leading_digits` digits,
+    /// derived from n * log10(φ) - log10(√5) evaluated with high-precision fixed-point
+    /// arithmetic. This works for indices far beyond what [`Fib::single`] can compute.
+    ///
+    /// # Arguments
+    ///
+    /// * `n` - The index of the Fibonacci number, as a `u128`, `BigUint` or other unsigned integer
+    /// * `leading_digits` - The number of leading digits to compute (at least one is always returned)
+    ///
+    /// # Returns
+    ///
+    /// * A `FibApprox` holding the digit count and leading digits of F(n)
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use fib_rs::Fib;
+    /// use num_bigint::BigUint;
+    ///
+    /// // F(100) = 354224848179261915075
+    /// let approx = Fib::approx(100u32, 5);
+    /// assert_eq!(approx.digit_count(), &BigUint::from(21u32));
+    /// assert_eq!(approx.to_string(), "3.5422e20");
+    ///
+    /// // Indices far out of reach of Fib::single
+    /// let n = BigUint::from(10u32).pow(25);
+    /// assert_eq!(Fib::approx(n, 3).to_string(), "3.49e2089876402499787337692720");
+    /// ```
+    #[must_use]
+    pub fn approx<N: Into<BigUint>>(n: N, leading_digits: u32) -> FibApprox {
+        let n = n.into();
+        let leading_digits = leading_digits.max(1);
+
+        // Working precision: the index size plus the requested digits plus guard bits
+        let mut precision = n.bits() + u64::from(leading_digits) * 10 / 3 + GUARD_BITS;
+        loop {
+            if n <= BigUint::from(EXACT_INDEX_BOUND) || BigUint::from(precision) >= n {
+                return approx_exact(&n, leading_digits);
+            }
+            if let Some(approx) = approx_with_precision(&n, leading_digits, precision) {
+                return approx;
+            }
+            precision += precision / 2;
+        }
+    }
+
+    /// Calculate the exact number of bits of the nth Fibonacci number without computing it.
+    ///
+    /// Uses interval arithmetic on n * log2(φ) - log2(√5), so the cost grows only with the
+    /// size of the index. Matches `Fib::single(n).bits()`, including 0 bits for F(0) = 0.
+    ///
+    /// # Arguments
+    ///
+    /// * `n` - The index of the Fibonacci number
+    ///
+    /// # Returns
+    ///
+    /// * The bit length of F(n)
+    ///
+    /// # Panics
+    ///
+    /// Panics if F(n) lies within a relative 2^-65536 of a power of 2. F(n) is never
+    /// computed for large n, so the length must settle by then; since n * log(φ) - log(√5) is
+    /// equidistributed modulo log(2), no index below 2^128 is expected to come that close.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use fib_rs::Fib;
+    ///
+    /// assert_eq!(Fib::bit_len(10), 6); // F(10) = 55 = 0b110111
+    /// assert_eq!(Fib::bit_len(10_000), u128::from(Fib::single(10_000).bits()));
+    /// assert_eq!(Fib::bit_len(1_000_000_000_000), 694_241_913_630);
+    /// ```
+    #[must_use]
+    pub fn bit_len(n: u128) -> u128 {
+        exact_len(n, 2)
+    }
+
+    /// Calculate the exact number of decimal digits of the nth Fibonacci number without computing it.
+    ///
+    /// Uses interval arithmetic on n * log10(φ) - log10(√5), so the cost grows only with the
+    /// size of the index. Matches `Fib::single(n).to_string().len()`, including 1 digit for F(0) = 0.
+    ///
+    /// # Arguments
+    ///
+    /// * `n` - The index of the Fibonacci number
+    ///
+    /// # Returns
+    ///
+    /// * The decimal digit count of F(n)
+    ///
+    /// # Panics
+    ///
+    /// Panics if F(n) lies within a relative 2^-65536 of a power of 10. F(n) is never
+    /// computed for large n, so the length must settle by then; since n * log(φ) - log(√5) is
+    /// equidistributed modulo log(10), no index below 2^128 is expected to come that close.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use fib_rs::Fib;
+    ///
+    /// assert_eq!(Fib::decimal_len(100), 21); // F(100) = 354224848179261915075
+    /// assert_eq!(Fib::decimal_len(1_000_000_000_000), 208_987_640_250);
+    /// ```
+    #[must_use]
+    pub fn decimal_len(n: u128) -> u128 {
+        exact_len(n, 10)
+    }
+}
+
+/// Compute the length of F(n) in the given base (2 or 10).
+fn exact_len(n: u128, base: u32) -> u128 {
+    exact_len_from(n, base, BigUint::from(n).bits() + GUARD_BITS)
+}
+
+/// Compute the length of F(n) in the given base (2 or 10), starting the enclosure at
+/// `precision` fractional bits.
+fn exact_len_from(n: u128, base: u32, mut precision: u64) -> u128 {
+    if n <= u128::from(EXACT_INDEX_BOUND) {
+        return exact_small_len(n, base);
+    }
+    loop {
+        // Beyond this precision computing F(n) is cheaper than the logarithms; as the precision
+        // is capped, this only happens for indices below the cap
+        if u128::from(precision) >= n {
+            return exact_small_len(n, base);
+        }
+        let (lower, upper) = len_bounds(n, base, precision);
+        if lower == upper {
+            return lower;
+        }
+        assert!(
+            precision < MAX_LEN_PRECISION,
+            "length of F({n}) in base {base} not settled at {MAX_LEN_PRECISION} bits"
+        );
+        precision = (2 * precision).min(MAX_LEN_PRECISION);
+    }
+}
+
+/// Compute the length of F(n) in the given base (2 or 10) from its value.
+fn exact_small_len(n: u128, base: u32) -> u128 {
+    let value = Fib::single(n);
+    if base == 2 {
+        u128::from(value.bits())
+    } else {
+        value.to_string().len() as u128
+    }
+}
+
+/// Bound the length of F(n), for n above [`EXACT_INDEX_BOUND`], in the given base (2 or 10) by
+/// an enclosure of its logarithm with `precision` fractional bits.
+fn len_bounds(n: u128, base: u32, precision: u64) -> (u128, u128) {
+    let logs = Logarithms::new(precision);
+    let ln_base = if base == 2 { &logs.ln2 } else { &logs.ln10 };
+    let (log, error) = logs.log_fib(&BigUint::from(n), ln_base);
+
+    // The length is floor(log_B F(n)) + 1
+    let len = |bound: BigUint| {
+        (bound >> precision)
+            .to_u128()
+            .expect("length of F(n) fits in u128")
+            + 1
+    };
+    (len(&log - &error), len(&log + &error))
+}
+
+/// Compute the approximation exactly from F(n) for small indices.
+fn approx_exact(n: &BigUint, leading_digits: u32) -> FibApprox {
+    let index = n
+        .to_u128()
+        .expect("exact path is only taken for small indices");
+    let decimal = Fib::single(index).to_string();
+    let kept = decimal.len().min(leading_digits as usize);
+    FibApprox {
+        digit_count: BigUint::from(decimal.len()),
+        leading_digits: decimal[..kept].parse().expect("decimal digits parse"),
+    }
+}
+
+/// Try to certify the approximation at the given precision.
+///
+/// Returns `None` when the error bound still straddles a digit boundary.
+fn approx_with_precision(n: &BigUint, leading_digits: u32, precision: u64) -> Option<FibApprox> {
+    let logs = Logarithms::new(precision);
+    let (log10, error) = logs.log_fib(n, &logs.ln10);
+
+    // Integer part: the digit count is floor(log10 F(n)) + 1
+    let lower = &log10 - &error;
+    let upper = &log10 + &error;
+    let digit_count = (&lower >> precision) + 1u32;
+    if (&upper >> precision) + 1u32 != digit_count {
+        return None;
+    }
+
+    // Fractional part f: the leading digits are floor(10^(f + k - 1))
+    let mask = (BigUint::one() << precision) - 1u32;
+    let fraction = &log10 & &mask;
+    if fraction < error || fraction > &mask - &error {
+        return None;
+    }
+    let (mantissa, mantissa_error) = logs.exp10(&fraction, &error);
+    let scale = BigUint::from(10u32).pow(leading_digits - 1);
+    let low = ((&mantissa - &mantissa_error) * &scale) >> precision;
+    let high = ((&mantissa + &mantissa_error) * &scale) >> precision;
+    (low == high).then_some(FibApprox {
+        digit_count,
+        leading_digits: low,
+    })
+}
+
+/// Natural logarithms in binary fixed point with `precision` fractional bits.
+///
+/// Every constant is within `error` units in the last place of its true value.
+struct Logarithms {
+    precision: u64,
+    error: BigUint,
+    ln_phi: BigUint,
+    ln2: BigUint,
+    ln10: BigUint,
+    ln_sqrt5: BigUint,
+}
+
+impl Logarithms {
+    /// Evaluate ln(φ), ln(2), ln(10) and ln(√5) with `precision` fractional bits.
+    fn new(precision: u64) -> Self {
+        let one = BigUint::one() << precision;
+
+        // ln(φ) = 2 * atanh(√5 - 2), since (1 + x) / (1 - x) = φ for x = φ^(-3) = √5 - 2
+        let sqrt5 = (BigUint::from(5u32) << (2 * precision)).sqrt();
+        let x = sqrt5 - (&one << 1);
+        let ln_phi = atanh(&x, precision) << 1;
+
+        // ln(2) = 2 * atanh(1/3) and ln(5/4) = 2 * atanh(1/9)
+        let ln2 = atanh(&(&one / 3u32), precision) << 1;
+        let ln5_4 = atanh(&(&one / 9u32), precision) << 1;
+
+        // ln(10) = 3 * ln(2) + ln(5/4) and ln(√5) = ln(2) + ln(5/4) / 2
+        let ln10 = &ln2 * 3u32 + &ln5_4;
+        let ln_sqrt5 = &ln2 + (&ln5_4 >> 1);
+
+        Self {
+            precision,
+            // Each series contributes at most two units per term, about precision / 2 terms
+            error: BigUint::from(8 * precision),
+            ln_phi,
+            ln2,
+            ln10,
+            ln_sqrt5,
+        }
+    }
+
+    /// Compute the base-b logarithm (n * ln(φ) - ln(√5)) / ln(b) of F(n) for n ≥ 2, with its error bound.
+    fn log_fib(&self, n: &BigUint, ln_base: &BigUint) -> (BigUint, BigUint) {
+        let ln_fib = n * &self.ln_phi - &self.ln_sqrt5;
+        let ln_error = (n + 1u32) * &self.error;
+
+        let value = (&ln_fib << self.precision) / ln_base;
+        // Dividing by ln(b) > 1/2 at most doubles the error; add the quotient's error terms
+        // and one unit for the neglected δ < φ^(-2n), valid since precision < n
+        let error = (&ln_error << 1) + ((&value * &self.error) >> (self.precision - 2)) + 2u32;
+        (value, error)
+    }
+
+    /// Compute 10^f for a fixed-point fraction 0 ≤ f < 1 known to within `f_error` units.
+    fn exp10(&self, fraction: &BigUint, f_error: &BigUint) -> (BigUint, BigUint) {
+        let precision = self.precision;
+        let y = (fraction * &self.ln10) >> precision;
+
+        // exp(y) = exp(y / 2^r)^(2^r), with the series on the reduced argument
+        let z = y >> EXP_REDUCTION_STEPS;
+        let mut sum = BigUint::one() << precision;
+        let mut term = sum.clone();
+        let mut terms = 0u64;
+        for j in 1u32.. {
+            term = ((term * &z) >> precision) / j;
+            if term.is_zero() {
+                break;
+            }
+            sum += &term;
+            terms += 1;
+        }
+        for _ in 0..EXP_REDUCTION_STEPS {
+            sum = (&sum * &sum) >> precision;
+        }
+
+        // Squaring doubles relative errors; the result is below 10 and d(10^f)/df < 24
+        let series_error = BigUint::from((terms + 2) * 10) << EXP_REDUCTION_STEPS;
+        let error = series_error + (f_error + &self.error) * 24u32;
+        (sum, error)
+    }
+}
+
+/// Compute atanh(x) = x + x^3/3 + x^5/5 + ... for a fixed-point 0 ≤ x < 1/2.
+fn atanh(x: &BigUint, precision: u64) -> BigUint {
+    let x2 = (x * x) >> precision;
+    let mut power = x.clone();
+    let mut sum = x.clone();
+    for k in 1u32.. {
+        power = (power * &x2) >> precision;
+        if power.is_zero() {
+            break;
+        }
+        sum += &power / (2 * k + 1);
+    }
+    sum
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn matches_exact_values() {
+        // Indices above the exact bound exercise the fixed-point path
+        for n in [1_001u32, 1_234, 2_500, 4_096, 7_919, 10_000] {
+            let decimal = Fib::single(u128::from(n)).to_string();
+            let approx = Fib::approx(n, 12);
+            assert_eq!(
+                approx.digit_count(),
+                &BigUint::from(decimal.len()),
+                "F({n})"
+            );
+            assert_eq!(approx.leading_digits().to_string(), decimal[..12], "F({n})");
+        }
+    }
+
+    #[test]
+    fn small_indices() {
+        assert_eq!(Fib::approx(0u32, 3).to_string(), "0e0");
+        assert_eq!(Fib::approx(10u32, 1).to_string(), "5e1");
+        assert_eq!(Fib::approx(10u32, 5).to_string(), "5.5e1");
+        assert_eq!(
+            Fib::approx(100u32, 1).leading_digits(),
+            &BigUint::from(3u32)
+        );
+    }
+
+    #[test]
+    fn astronomically_large_indices() {
+        // Reference values from 120-digit decimal arithmetic
+        let approx = Fib::approx(BigUint::from(10u32).pow(30), 20);
+        assert_eq!(approx.leading_digits().to_string(), "16066889997798659155");
+        assert_eq!(
+            approx.digit_count().to_string(),
+            "208987640249978733769272089238"
+        );
+
+        let approx = Fib::approx(BigUint::from(10u32).pow(100), 20);
+        assert_eq!(approx.leading_digits().to_string(), "62449911286460687648");
+        assert_eq!(
+            approx.digit_count().to_string(),
+            "2089876402499787337692720892375554168224592399182109535392875613974104853496745963277658556235103535"
+        );
+    }
+
+    #[test]
+    fn lengths_match_exact_values() {
+        for n in (0..1_200).chain([1_999, 2_048, 5_000, 9_999]) {
+            let value = Fib::single(n);
+            assert_eq!(Fib::bit_len(n), u128::from(value.bits()), "F({n})");
+            assert_eq!(
+                Fib::decimal_len(n),
+                value.to_string().len() as u128,
+                "F({n})"
+            );
+        }
+    }
+
+    #[test]
+    fn straddling_enclosures_are_refined() {
+        // At 16 bits the enclosure often straddles a power of the base, and raising the
+        // precision must settle every such index on the exact length
+        let mut straddling = 0;
+        for n in 1_001..1_400 {
+            let value = Fib::single(n);
+            for (base, expected) in [
+                (2, u128::from(value.bits())),
+                (10, value.to_string().len() as u128),
+            ] {
+                let (lower, upper) = len_bounds(n, base, 16);
+                if lower != upper {
+                    straddling += 1;
+                }
+                assert_eq!(
+                    exact_len_from(n, base, 16),
+                    expected,
+                    "F({n}) in base {base}"
+                );
+            }
+        }
+        assert!(straddling > 0);
+    }
+
+    #[test]
+    fn lengths_above_the_precision_cap_settle_without_the_value() {
+        // Starting from 16 bits, every straddling enclosure is settled by refinement alone,
+        // since the exact fallback is out of reach for indices above the cap
+        let start = u128::from(MAX_LEN_PRECISION) + 1;
+        for n in start..start + 200 {
+            let value = Fib::single(n);
+            assert_eq!(exact_len_from(n, 2, 16), u128::from(value.bits()), "F({n})");
+            assert_eq!(
+                exact_len_from(n, 10, 16),
+                value.to_string().len() as u128,
+                "F({n})"
+            );
+        }
+    }
+
+    #[test]
+    fn logarithm_constants() {
+        let logs = Logarithms::new(128);
+        let one = 2f64.powi(128);
+        let to_f64 = |x: &BigUint| x.to_f64().unwrap() / one;
+        assert!((to_f64(&logs.ln_phi) - 0.481_211_825_059_603_4).abs() < 1e-15);
+        assert!((to_f64(&logs.ln2) - std::f64::consts::LN_2).abs() < 1e-15);
+        assert!((to_f64(&logs.ln10) - std::f64::consts::LN_10).abs() < 1e-15);
+    }
+}
@@ -0,0 +1,527 @@
+//! Contiguous storage for ranges of Fibonacci numbers.
+//!
+//! A `Vec<BigUint>` holds one heap allocation per value, which fragments memory for ranges of
+//! millions of entries. [`FibRange`] instead keeps the 64-bit limbs of every value in a single
+//! buffer with an offset table. Each parallel chunk writes its values straight into its own region
+//! of that buffer, adding the two previous limb slices in place, so generation allocates nothing
+//! per value either.
+
+use std::{
+    fmt,
+    iter::{Enumerate, FusedIterator},
+    slice::Windows,
+    sync::atomic::{AtomicU64, Ordering},
+};
+
+use num_bigint::BigUint;
+use rayon::prelude::*;
+
+use crate::{
+    CONTROL_CHECK_INTERVAL, Chunking, Control, Fib, FibError, error, estimate::LOG2_PHI_UPPER,
+};
+
+/// An ordered range of consecutive Fibonacci numbers stored in one contiguous buffer.
+///
+/// Values are borrowed as [`FibView`]s and converted to `BigUint` only on demand.
+///
+/// # Examples
+///
+/// ```
+/// use fib_rs::Fib;
+///
+/// let range = Fib::try_range_arena(10, 20).unwrap();
+/// assert_eq!(range.len(), 11);
+/// assert_eq!(range.get(12).unwrap().to_string(), "144");
+/// assert_eq!(range.to_vec(), Fib::range(10, 20));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FibRange {
+    /// Index of the first value.
+    start: u128,
+    /// Little-endian limbs of all values, back to back.
+    limbs: Vec<u64>,
+    /// Start of every value in `limbs`, followed by the total length.
+    offsets: Vec<usize>,
+}
+
+/// A borrowed Fibonacci number within a [`FibRange`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FibView<'a> {
+    index: u128,
+    limbs: &'a [u64],
+}
+
+/// Iterator over the values of a [`FibRange`], created by [`FibRange::iter`].
+#[derive(Debug, Clone)]
+pub struct FibRangeIter<'a> {
+    start: u128,
+    limbs: &'a [u64],
+    offsets: Enumerate<Windows<'a, usize>>,
+}
+
+impl FibRange {
+    /// Index of the first value in the range.
+    #[must_use]
+    pub fn start(&self) -> u128 {
+        self.start
+    }
+
+    /// Index of the last value in the range.
+    #[must_use]
+    pub fn end(&self) -> u128 {
+        self.start + self.len() as u128 - 1
+    }
+
+    /// Number of values in the range.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.offsets.len() - 1
+    }
+
+    /// Whether the range holds no values. Ranges produced by this crate never are.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Borrow F(n), if `n` lies within the range.
+    ///
+    /// # Arguments
+    ///
+    /// * `n` - The index of the Fibonacci number, not its position in the range
+    ///
+    /// # Returns
+    ///
+    /// * `Some(view)` of F(n) for `start <= n <= end`, `None` otherwise
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use fib_rs::Fib;
+    ///
+    /// let range = Fib::try_range_arena(100, 200).unwrap();
+    /// assert_eq!(range.get(150).unwrap().to_biguint(), Fib::single(150));
+    /// assert!(range.get(99).is_none());
+    /// ```
+    #[must_use]
+    pub fn get(&self, n: u128) -> Option<FibView<'_>> {
+        let position = usize::try_from(n.checked_sub(self.start)?).ok()?;
+        let bounds = self.offsets.get(position..position + 2)?;
+        Some(FibView {
+            index: n,
+            limbs: &self.limbs[bounds[0]..bounds[1]],
+        })
+    }
+
+    /// Iterate over the values in ascending order of index.
+    #[must_use]
+    pub fn iter(&self) -> FibRangeIter<'_> {
+        FibRangeIter {
+            start: self.start,
+            limbs: &self.limbs,
+            offsets: self.offsets.windows(2).enumerate(),
+        }
+    }
+
+    /// Convert every value to a `BigUint`.
+    ///
+    /// # Returns
+    ///
+    /// * A `Vec<BigUint>` equal to [`Fib::range`] over the same indices
+    #[must_use]
+    pub fn to_vec(&self) -> Vec<BigUint> {
+        self.iter().map(FibView::to_biguint).collect()
+    }
+
+    /// The limbs of all values, back to back, in ascending order of index.
+    #[must_use]
+    pub fn limbs(&self) -> &[u64] {
+        &self.limbs
+    }
+}
+
+impl<'a> IntoIterator for &'a FibRange {
+    type Item = FibView<'a>;
+    type IntoIter = FibRangeIter<'a>;
+
+    fn into_iter(self) -> FibRangeIter<'a> {
+        self.iter()
+    }
+}
+
+impl From<FibRange> for Vec<BigUint> {
+    fn from(range: FibRange) -> Self {
+        range.to_vec()
+    }
+}
+
+impl<'a> FibView<'a> {
+    /// Index n of this value F(n).
+    #[must_use]
+    pub fn index(self) -> u128 {
+        self.index
+    }
+
+    /// Little-endian 64-bit limbs, without trailing zeros. F(0) has none.
+    #[must_use]
+    pub fn limbs(self) -> &'a [u64] {
+        self.limbs
+    }
+
+    /// Number of bits in the value.
+    #[must_use]
+    pub fn bits(self) -> u64 {
+        self.limbs.last().map_or(0, |&top| {
+            self.limbs.len() as u64 * 64 - u64::from(top.leading_zeros())
+        })
+    }
+
+    /// Copy the value into a `BigUint`.
+    #[must_use]
+    pub fn to_biguint(self) -> BigUint {
+        #[allow(clippy::cast_possible_truncation)] // Splitting each limb into its two halves
+        let digits = self
+            .limbs
+            .iter()
+            .flat_map(|&limb| [limb as u32, (limb >> 32) as u32])
+            .collect();
+        BigUint::new(digits)
+    }
+}
+
+impl fmt::Display for FibView<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(&self.to_biguint(), f)
+    }
+}
+
+impl<'a> Iterator for FibRangeIter<'a> {
+    type Item = FibView<'a>;
+
+    fn next(&mut self) -> Option<FibView<'a>> {
+        let (position, bounds) = self.offsets.next()?;
+        Some(self.view(position, bounds))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.offsets.size_hint()
+    }
+}
+
+impl DoubleEndedIterator for FibRangeIter<'_> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        let (position, bounds) = self.offsets.next_back()?;
+        Some(self.view(position, bounds))
+    }
+}
+
+impl ExactSizeIterator for FibRangeIter<'_> {}
+
+impl FusedIterator for FibRangeIter<'_> {}
+
+impl<'a> FibRangeIter<'a> {
+    fn view(&self, position: usize, bounds: &[usize]) -> FibView<'a> {
+        FibView {
+            index: self.start + position as u128,
+            limbs: &self.limbs[bounds[0]..bounds[1]],
+        }
+    }
+}
+
+impl Fib {
+    /// Generate Fibonacci numbers for indices in the given inclusive range into a single
+    /// contiguous buffer.
+    ///
+    /// Computes the same values as [`Fib::try_range`] with the same parallel chunks, but without
+    /// one heap allocation per value.
+    ///
+    /// # Arguments
+    ///
+    /// * `start` - The starting index of the range
+    /// * `end` - The ending index of the range (inclusive)
+    ///
+    /// # Returns
+    ///
+    /// * A [`FibRange`] holding F(start) through F(end)
+    ///
+    /// # Errors
+    ///
+    /// * `FibError::InvertedRange` if `end < start`
+    /// * `FibError::CountOverflow` if the number of values does not fit in `usize`
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use fib_rs::{Fib, FibError};
+    ///
+    /// let range = Fib::try_range_arena(0, 1_000).unwrap();
+    /// assert_eq!(range.iter().last().unwrap().to_biguint(), Fib::single(1_000));
+    /// assert_eq!(
+    ///     Fib::try_range_arena(10, 3),
+    ///     Err(FibError::InvertedRange { start: 10, end: 3 })
+    /// );
+    /// ```
+    pub fn try_range_arena(start: u128, end: u128) -> Result<FibRange, FibError> {
+        Self::range_arena_with(start, end, &Control::new())
+    }
+
+    /// Generate Fibonacci numbers for indices in the given inclusive range into a single
+    /// contiguous buffer, observing a cancellation token, a deadline and a progress callback.
+    ///
+    /// Progress and checks behave as for [`Fib::range_with`].
+    ///
+    /// # Arguments
+    ///
+    /// * `start` - The starting index of the range
+    /// * `end` - The ending index of the range (inclusive)
+    /// * `control` - The cancellation token, deadline and progress callback to observe
+    ///
+    /// # Returns
+    ///
+    /// * A [`FibRange`] holding F(start) through F(end)
+    ///
+    /// # Errors
+    ///
+    /// * `FibError::InvertedRange` if `end < start`
+    /// * `FibError::CountOverflow` if the number of values does not fit in `usize`
+    /// * `FibError::Cancelled` if the token is cancelled before the computation completes
+    /// * `FibError::DeadlineExceeded` if the deadline passes before the computation completes
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use fib_rs::{CancellationToken, Control, Fib, FibError};
+    ///
+    /// let token = CancellationToken::new();
+    /// token.cancel();
+    /// let cancelled = Control::new().cancel_with(token);
+    /// assert_eq!(
+    ///     Fib::range_arena_with(0, 1_000, &cancelled),
+    ///     Err(FibError::Cancelled)
+    /// );
+    /// ```
+    pub fn range_arena_with(
+        start: u128,
+        end: u128,
+        control: &Control,
+    ) -> Result<FibRange, FibError> {
+        generate(start, end, Chunking::default(), control)
+    }
+}
+
+/// Generate F(start) through F(end) into a [`FibRange`], splitting the work by `chunking`.
+pub(crate) fn generate(
+    start: u128,
+    end: u128,
+    chunking: Chunking,
+    control: &Control,
+) -> Result<FibRange, FibError> {
+    let total_count = error::range_len(start, end)?;
+    control.check()?;
+    let (lens, seeds) = Fib::range_chunks::<BigUint>(start, total_count, chunking, control)?;
+
+    // Reserve an upper bound of limbs for every chunk, so chunks fill disjoint regions of one
+    // buffer. Since F(m + k) <= F(m + 1) * F(k + 1) < F(m + 1) * φ^k, the kth value of a chunk has
+    // at most bits(F(m + 1)) + k * log2(φ) + 1 bits; one more bit covers rounding.
+    let capacities: Vec<usize> = lens
+        .iter()
+        .zip(&seeds)
+        .map(|(&len, (_, next))| {
+            let seed_bits = next.bits();
+            (0..len)
+                .map(|k| {
+                    #[allow(
+                        clippy::cast_possible_truncation,
+                        clippy::cast_precision_loss,
+                        clippy::cast_sign_loss
+                    )]
+                    // Rounded-down product of a non-negative bound well below 2^64
+                    let growth = (k as f64 * LOG2_PHI_UPPER) as u64;
+                    limbs_for(seed_bits + growth + 2)
+                })
+                .sum()
+        })
+        .collect();
+    let mut limbs = vec![0u64; capacities.iter().sum()];
+    let mut regions = Vec::with_capacity(capacities.len());
+    let mut rest = limbs.as_mut_slice();
+    for &capacity in &capacities {
+        let (region, tail) = rest.split_at_mut(capacity);
+        regions.push(region);
+        rest = tail;
+    }
+
+    let total_chunks = lens.len() as u64;
+    let completed_chunks = AtomicU64::new(0);
+    let value_lens: Vec<Vec<usize>> = regions
+        .into_par_iter()
+        .zip(lens)
+        .zip(seeds)
+        .map(|((region, len), seed)| {
+            let value_lens = fill_chunk(region, len, &seed, control)?;
+            let completed = completed_chunks.fetch_add(1, Ordering::Relaxed) + 1;
+            control.report(completed, total_chunks);
+            Ok(value_lens)
+        })
+        .collect::<Result<_, FibError>>()?;
+
+    // Close the gaps left by overestimated capacities and record where each value starts
+    let mut offsets = Vec::with_capacity(total_count + 1);
+    offsets.push(0);
+    let mut written = 0;
+    let mut region_start = 0;
+    for (capacity, value_lens) in capacities.into_iter().zip(value_lens) {
+        let used: usize = value_lens.iter().sum();
+        limbs.copy_within(region_start..region_start + used, written);
+        for len in value_lens {
+            written += len;
+            offsets.push(written);
+        }
+        region_start += capacity;
+    }
+    limbs.truncate(written);
+    limbs.shrink_to_fit();
+
+    Ok(FibRange {
+        start,
+        limbs,
+        offsets,
+    })
+}
+
+/// Number of 64-bit limbs needed for a value of `bits` bits.
+fn limbs_for(bits: u64) -> usize {
+    usize::try_from(bits.div_ceil(64)).expect("limb count fits in usize")
+}
+
+/// Write `len` consecutive values starting from the seed (F(m), F(m+1)) into `region`.
+///
+/// Returns the number of limbs of each value.
+fn fill_chunk(
+    region: &mut [u64],
+    len: usize,
+    (first, second): &(BigUint, BigUint),
+    control: &Control,
+) -> Result<Vec<usize>, FibError> {
+    let interruptible = control.is_interruptible();
+    let mut value_lens = Vec::with_capacity(len);
+    let mut position = 0;
+    for seed in [first, second].into_iter().take(len) {
+        for limb in seed.iter_u64_digits() {
+            region[position] = limb;
+            position += 1;
+        }
+        value_lens.push(seed.iter_u64_digits().len());
+    }
+
+    // Extend the chunk with F(n+2) = F(n+1) + F(n), reading both terms from the region itself
+    for remaining in (0..len.saturating_sub(2)).rev() {
+        if interruptible && remaining.is_multiple_of(CONTROL_CHECK_INTERVAL) {
+            control.check()?;
+        }
+        let [.., before, last] = value_lens[..] else {
+            unreachable!("a chunk of three or more values starts with two seeds")
+        };
+        let (done, free) = region.split_at_mut(position);
+        let written = add_limbs(
+            &done[position - before - last..position - last],
+            &done[position - last..],
+            free,
+        );
+        value_lens.push(written);
+        position += written;
+    }
+    Ok(value_lens)
+}
+
+/// Write `smaller + larger` into `out`, where `smaller` has no more limbs than `larger`.
+///
+/// Returns the number of limbs of the sum.
+fn add_limbs(smaller: &[u64], larger: &[u64], out: &mut [u64]) -> usize {
+    let mut carry = false;
+    for (i, &limb) in larger.iter().enumerate() {
+        let (sum, overflow) = limb.overflowing_add(smaller.get(i).copied().unwrap_or(0));
+        let (sum, carried) = sum.overflowing_add(u64::from(carry));
+        out[i] = sum;
+        carry = overflow || carried;
+    }
+    if carry {
+        out[larger.len()] = 1;
+        larger.len() + 1
+    } else {
+        larger.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::num::NonZeroUsize;
+
+    use super::*;
+
+    #[test]
+    fn arena_matches_range() {
+        for (start, end) in [
+            (0, 0),
+            (0, 1),
+            (0, 200),
+            (5, 7),
+            (1_000, 3_000),
+            (99_990, 100_010),
+        ] {
+            let range = Fib::try_range_arena(start, end).unwrap();
+            assert_eq!(range.start(), start);
+            assert_eq!(range.end(), end);
+            assert_eq!(range.to_vec(), Fib::range(start, end), "{start}..={end}");
+        }
+    }
+
+    #[test]
+    fn chunked_arena_is_compact() {
+        let control = Control::new();
+        for chunking in [
+            Chunking::Balanced,
+            Chunking::PerThread,
+            Chunking::ChunkLen(NonZeroUsize::new(7).unwrap()),
+            Chunking::ChunkCount(NonZeroUsize::new(13).unwrap()),
+        ] {
+            let range = generate(300, 2_000, chunking, &control).unwrap();
+            let expected = Fib::range(300, 2_000);
+            assert_eq!(range.to_vec(), expected, "{chunking:?}");
+            let total: usize = expected.iter().map(|v| v.iter_u64_digits().len()).sum();
+            assert_eq!(range.limbs().len(), total);
+        }
+    }
+
+    #[test]
+    fn views_and_iteration() {
+        let range = Fib::try_range_arena(90, 100).unwrap();
+        assert!(range.get(89).is_none());
+        assert!(range.get(101).is_none());
+        let view = range.get(94).unwrap();
+        assert_eq!(view.index(), 94);
+        assert_eq!(view.limbs().len(), 2);
+        assert_eq!(view.bits(), Fib::single(94).bits());
+        assert_eq!(view.to_string(), Fib::single(94).to_string());
+
+        let iter = range.iter();
+        assert_eq!(iter.len(), 11);
+        let indices: Vec<u128> = iter.rev().map(FibView::index).collect();
+        assert_eq!(indices, (90..=100).rev().collect::<Vec<_>>());
+        assert_eq!((&range).into_iter().nth(4), Some(view));
+        assert_eq!(Vec::<BigUint>::from(range), Fib::range(90, 100));
+
+        let zero = Fib::try_range_arena(0, 0).unwrap();
+        assert_eq!(zero.get(0).unwrap().bits(), 0);
+        assert_eq!(zero.get(0).unwrap().to_string(), "0");
+    }
+
+    #[test]
+    fn limb_addition_carries() {
+        let mut out = [0; 3];
+        assert_eq!(add_limbs(&[1], &[u64::MAX, u64::MAX], &mut out), 3);
+        assert_eq!(out, [0, 0, 1]);
+        assert_eq!(add_limbs(&[], &[5], &mut out), 1);
+        assert_eq!(out[0], 5);
+    }
+}
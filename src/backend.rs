@@ -0,0 +1,346 @@
+//! Pluggable big-integer backends.
+//!
+//! The fast doubling and range algorithms only need a handful of operations on natural numbers,
+//! collected in the [`Backend`] trait. `num-bigint`'s `BigUint` is the default backend; enabling
+//! the `malachite` or `ibig` cargo feature adds implementations for `malachite_nz::natural::Natural`
+//! and `ibig::UBig`, so results can be produced directly in those types instead of being converted
+//! value by value.
+
+use std::convert::Infallible;
+
+use num_bigint::BigUint;
+
+use crate::{Chunking, Control, Fib, FibError, mul};
+
+/// An arbitrary-precision natural number type that Fibonacci numbers can be computed in.
+///
+/// Implement this trait to run [`Fib::single_in`] and [`Fib::try_range_in`] on another
+/// big-integer library. Subtractions are only ever called with a result that is not negative.
+///
+/// # Examples
+///
+/// ```
+/// use fib_rs::{Backend, Fib};
+/// use num_bigint::BigUint;
+///
+/// let value: BigUint = Fib::single_in(100);
+/// assert_eq!(value, Fib::single(100));
+/// assert_eq!(BigUint::from_biguint(&value).to_biguint(), value);
+/// ```
+pub trait Backend: Clone + Default + Send + Sync {
+    /// Convert a small value.
+    fn from_u32(value: u32) -> Self;
+
+    /// Convert a value that fits in a `u128`, such as a Fibonacci number up to F(186).
+    #[must_use]
+    fn from_u128(value: u128) -> Self {
+        Self::from_biguint(&BigUint::from(value))
+    }
+
+    /// Convert from a `BigUint`.
+    fn from_biguint(value: &BigUint) -> Self;
+
+    /// Convert to a `BigUint`.
+    fn to_biguint(&self) -> BigUint;
+
+    /// Compute `self + other`.
+    #[must_use]
+    fn add(&self, other: &Self) -> Self;
+
+    /// Compute `self * other`.
+    #[must_use]
+    fn mul(&self, other: &Self) -> Self;
+
+    /// Compute `self^2`.
+    #[must_use]
+    fn square(&self) -> Self {
+        self.mul(self)
+    }
+
+    /// Compute `a^2` and `b^2`, which backends may do concurrently.
+    fn square_pair(a: &Self, b: &Self) -> (Self, Self) {
+        (a.square(), b.square())
+    }
+
+    /// Set `self` to `self + other`.
+    fn add_assign(&mut self, other: &Self);
+
+    /// Set `self` to `self - other`.
+    fn sub_assign(&mut self, other: &Self);
+
+    /// Set `self` to `minuend - self`.
+    fn sub_from(&mut self, minuend: &Self);
+
+    /// Set `self` to `self + value`.
+    fn add_u32(&mut self, value: u32);
+
+    /// Set `self` to `self - value`.
+    fn sub_u32(&mut self, value: u32);
+
+    /// Set `self` to `self * value`.
+    fn mul_u32(&mut self, value: u32);
+}
+
+impl Backend for BigUint {
+    fn from_u32(value: u32) -> Self {
+        Self::from(value)
+    }
+
+    fn from_u128(value: u128) -> Self {
+        Self::from(value)
+    }
+
+    fn from_biguint(value: &BigUint) -> Self {
+        value.clone()
+    }
+
+    fn to_biguint(&self) -> BigUint {
+        self.clone()
+    }
+
+    fn add(&self, other: &Self) -> Self {
+        self + other
+    }
+
+    fn mul(&self, other: &Self) -> Self {
+        mul::mul(self, other)
+    }
+
+    fn square_pair(a: &Self, b: &Self) -> (Self, Self) {
+        mul::square_pair(a, b)
+    }
+
+    fn add_assign(&mut self, other: &Self) {
+        *self += other;
+    }
+
+    fn sub_assign(&mut self, other: &Self) {
+        *self -= other;
+    }
+
+    fn sub_from(&mut self, minuend: &Self) {
+        // Subtracting an owned value reuses its buffer for the difference
+        *self = minuend - std::mem::take(self);
+    }
+
+    fn add_u32(&mut self, value: u32) {
+        *self += value;
+    }
+
+    fn sub_u32(&mut self, value: u32) {
+        *self -= value;
+    }
+
+    fn mul_u32(&mut self, value: u32) {
+        *self *= value;
+    }
+}
+
+#[cfg(feature = "malachite")]
+impl Backend for malachite_nz::natural::Natural {
+    fn from_u32(value: u32) -> Self {
+        Self::from(value)
+    }
+
+    fn from_u128(value: u128) -> Self {
+        Self::from(value)
+    }
+
+    fn from_biguint(value: &BigUint) -> Self {
+        use malachite_base::num::conversion::traits::PowerOf2Digits;
+        Self::from_power_of_2_digits_asc(64, value.iter_u64_digits())
+            .expect("64-bit digits are valid")
+    }
+
+    fn to_biguint(&self) -> BigUint {
+        use malachite_base::num::conversion::traits::PowerOf2Digits;
+        BigUint::new(self.to_power_of_2_digits_asc(32))
+    }
+
+    fn add(&self, other: &Self) -> Self {
+        self + other
+    }
+
+    fn mul(&self, other: &Self) -> Self {
+        self * other
+    }
+
+    fn square(&self) -> Self {
+        use malachite_base::num::arithmetic::traits::Square;
+        Square::square(self)
+    }
+
+    fn add_assign(&mut self, other: &Self) {
+        *self += other;
+    }
+
+    fn sub_assign(&mut self, other: &Self) {
+        *self -= other;
+    }
+
+    fn sub_from(&mut self, minuend: &Self) {
+        *self = minuend - &*self;
+    }
+
+    fn add_u32(&mut self, value: u32) {
+        *self += Self::from(value);
+    }
+
+    fn sub_u32(&mut self, value: u32) {
+        *self -= Self::from(value);
+    }
+
+    fn mul_u32(&mut self, value: u32) {
+        *self *= Self::from(value);
+    }
+}
+
+#[cfg(feature = "ibig")]
+impl Backend for ibig::UBig {
+    fn from_u32(value: u32) -> Self {
+        Self::from(value)
+    }
+
+    fn from_u128(value: u128) -> Self {
+        Self::from(value)
+    }
+
+    fn from_biguint(value: &BigUint) -> Self {
+        Self::from_le_bytes(&value.to_bytes_le())
+    }
+
+    fn to_biguint(&self) -> BigUint {
+        BigUint::from_bytes_le(&self.to_le_bytes())
+    }
+
+    fn add(&self, other: &Self) -> Self {
+        self + other
+    }
+
+    fn mul(&self, other: &Self) -> Self {
+        self * other
+    }
+
+    fn add_assign(&mut self, other: &Self) {
+        *self += other;
+    }
+
+    fn sub_assign(&mut self, other: &Self) {
+        *self -= other;
+    }
+
+    fn sub_from(&mut self, minuend: &Self) {
+        *self = minuend - &*self;
+    }
+
+    fn add_u32(&mut self, value: u32) {
+        *self += value;
+    }
+
+    fn sub_u32(&mut self, value: u32) {
+        *self -= value;
+    }
+
+    fn mul_u32(&mut self, value: u32) {
+        *self *= value;
+    }
+}
+
+impl Fib {
+    /// Calculate the nth Fibonacci number directly in the big-integer type `B`.
+    ///
+    /// # Arguments
+    ///
+    /// * `n` - The index of the Fibonacci number to calculate
+    ///
+    /// # Returns
+    ///
+    /// * The nth Fibonacci number as a `B`
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use fib_rs::Fib;
+    /// use num_bigint::BigUint;
+    ///
+    /// assert_eq!(Fib::single_in::<BigUint>(10), BigUint::from(55u32));
+    /// ```
+    #[must_use]
+    pub fn single_in<B: Backend>(n: u128) -> B {
+        if let Some(value) = Self::single_u128(n) {
+            return B::from_u128(value);
+        }
+        let Ok(value) = Self::fib_single_steps(n, &mut || Ok::<_, Infallible>(()));
+        value
+    }
+
+    /// Generate Fibonacci numbers for indices in the given inclusive range directly in the
+    /// big-integer type `B`.
+    ///
+    /// Behaves like [`Fib::try_range`], computing chunks in parallel on the global rayon pool.
+    ///
+    /// # Arguments
+    ///
+    /// * `start` - The starting index of the range
+    /// * `end` - The ending index of the range (inclusive)
+    ///
+    /// # Returns
+    ///
+    /// * A `Vec<B>` containing ordered Fibonacci numbers for indices in the specified inclusive range.
+    ///
+    /// # Errors
+    ///
+    /// * `FibError::InvertedRange` if `end < start`
+    /// * `FibError::CountOverflow` if the number of values does not fit in `usize`
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use fib_rs::Fib;
+    /// use num_bigint::BigUint;
+    ///
+    /// let values = Fib::try_range_in::<BigUint>(3, 10).unwrap();
+    /// assert_eq!(values, Fib::range(3, 10));
+    /// ```
+    pub fn try_range_in<B: Backend>(start: u128, end: u128) -> Result<Vec<B>, FibError> {
+        Self::range_chunked(start, end, Chunking::default(), &Control::new())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Check that backend `B` agrees with `BigUint` on single values, ranges and conversions.
+    fn check_backend<B: Backend + PartialEq + std::fmt::Debug>() {
+        for n in (0..200).chain([1_000, 4_097, 100_000]) {
+            let value: B = Fib::single_in(n);
+            assert_eq!(value.to_biguint(), Fib::single(n), "F({n})");
+            assert_eq!(B::from_biguint(&value.to_biguint()), value);
+        }
+        let values: Vec<B> = Fib::try_range_in(990, 1_100).unwrap();
+        let values: Vec<_> = values.iter().map(B::to_biguint).collect();
+        assert_eq!(values, Fib::range(990, 1_100));
+        assert_eq!(
+            Fib::try_range_in::<B>(5, 4),
+            Err(FibError::InvertedRange { start: 5, end: 4 })
+        );
+    }
+
+    #[test]
+    fn biguint_backend() {
+        check_backend::<BigUint>();
+    }
+
+    #[cfg(feature = "malachite")]
+    #[test]
+    fn malachite_backend() {
+        check_backend::<malachite_nz::natural::Natural>();
+    }
+
+    #[cfg(feature = "ibig")]
+    #[test]
+    fn ibig_backend() {
+        check_backend::<ibig::UBig>();
+    }
+}
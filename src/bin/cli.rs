@@ -1,5 +1,10 @@
 use clap::{Parser, Subcommand};
-use fib_rs::Fib;
+use std::{
+    io::{self, BufWriter, Write},
+    path::PathBuf,
+};
+
+use fib_rs::{Algorithm, Fib, FibError, FileFormat, OutOfCore, RangeFormat, write_decimal};
 
 #[derive(Parser)]
 #[command(version)]
@@ -14,6 +19,9 @@ enum Commands {
     Single {
         /// The nth Fibonacci number to compute
         n: u128,
+        /// Algorithm to use: naive, matrix, fast-doubling or lucas
+        #[arg(long, default_value_t = Algorithm::FastDoubling)]
+        algorithm: Algorithm,
     },
     /// Calculate a range of Fibonacci numbers
     Range {
@@ -22,27 +30,77 @@ enum Commands {
         /// Ending index (inclusive)
         end: u128,
     },
+    /// Calculate a single Fibonacci number into a file, keeping operands on disk once they
+    /// outgrow the memory limit
+    File {
+        /// The nth Fibonacci number to compute
+        n: u128,
+        /// The file to write the value to
+        output: PathBuf,
+        /// Directory for scratch files
+        #[arg(long, default_value_os_t = std::env::temp_dir())]
+        scratch_dir: PathBuf,
+        /// Working memory limit in bytes
+        #[arg(long, default_value_t = 1 << 30)]
+        memory_limit: u64,
+        /// Write 64-bit little-endian limbs instead of decimal digits
+        #[arg(long)]
+        binary: bool,
+    },
 }
 
 fn main() {
     let cli = Cli::parse();
 
     match &cli.command {
-        Commands::Single { n } => {
-            let result = Fib::single(*n);
-            println!("F({n}) = {result}");
+        Commands::Single { n, algorithm } => {
+            let result = algorithm.single(*n);
+            let mut stdout = BufWriter::new(io::stdout());
+            let written = write!(stdout, "F({n}) = ")
+                .and_then(|()| write_decimal(&result, &mut stdout))
+                .and_then(|()| writeln!(stdout))
+                .and_then(|()| stdout.flush());
+            if let Err(error) = written
+                && error.kind() != io::ErrorKind::BrokenPipe
+            {
+                eprintln!("failed to write output: {error}");
+                std::process::exit(1);
+            }
         }
         Commands::Range { start, end } => {
-            let results = Fib::range(*start, *end);
-
-            if results.is_empty() {
-                eprintln!("Invalid range: end < start");
-                return;
+            let stdout = BufWriter::new(io::stdout());
+            match Fib::write_range(*start, *end, stdout, RangeFormat::Labeled) {
+                // The reader going away, e.g. when piped into `head`, is not an error
+                Ok(())
+                | Err(FibError::Io {
+                    kind: io::ErrorKind::BrokenPipe,
+                    ..
+                }) => {}
+                Err(FibError::InvertedRange { .. }) => eprintln!("Invalid range: end < start"),
+                Err(error @ FibError::Io { .. }) => {
+                    eprintln!("{error}");
+                    std::process::exit(1);
+                }
+                Err(error) => eprintln!("Invalid range: {error}"),
+            }
+        }
+        Commands::File {
+            n,
+            output,
+            scratch_dir,
+            memory_limit,
+            binary,
+        } => {
+            let config = OutOfCore::new(scratch_dir).memory_limit(*memory_limit);
+            let format = if *binary {
+                FileFormat::Binary
+            } else {
+                FileFormat::Decimal
+            };
+            if let Err(error) = Fib::single_to_file(*n, output, format, &config) {
+                eprintln!("{error}");
+                std::process::exit(1);
             }
-
-            (*start..=*end)
-                .zip(results.iter())
-                .for_each(|(i, result)| println!("F({i}) = {result}"));
         }
     }
 }
@@ -1,4 +1,4 @@
-use fib_rs::Fib;
+use fib_rs::{Fib, FibError, to_decimal};
 use leptos::prelude::*;
 use leptos_use::{UseToggleReturn, use_toggle, use_window};
 
@@ -84,24 +84,28 @@ fn Calculator(set_result: WriteSignal<Vec<String>>, is_single_mode: Signal<bool>
             match value.get() {
                 Ok(n) => {
                     let result = Fib::single(n);
-                    set_result.set(vec![format!("F({}) = {}", n, result)]);
+                    set_result.set(vec![format!("F({}) = {}", n, to_decimal(&result))]);
                 }
                 Err(_) => set_result.set(vec!["Please enter a valid number".to_string()]),
             }
         } else {
             match (start.get(), end.get()) {
-                (Ok(start_val), Ok(end_val)) if start_val > end_val => {
-                    set_result.set(vec!["Invalid range: end < start".to_string()]);
-                }
-                (Ok(start_val), Ok(end_val)) => {
-                    let results = Fib::range(start_val, end_val);
-                    let formatted = results
-                        .into_iter()
-                        .enumerate()
-                        .map(|(i, v)| format!("F({}) = {}", start_val + i as u128, v))
-                        .collect();
-                    set_result.set(formatted);
-                }
+                (Ok(start_val), Ok(end_val)) => match Fib::try_range(start_val, end_val) {
+                    Ok(results) => {
+                        let formatted = results
+                            .into_iter()
+                            .enumerate()
+                            .map(|(i, v)| {
+                                format!("F({}) = {}", start_val + i as u128, to_decimal(&v))
+                            })
+                            .collect();
+                        set_result.set(formatted);
+                    }
+                    Err(FibError::InvertedRange { .. }) => {
+                        set_result.set(vec!["Invalid range: end < start".to_string()]);
+                    }
+                    Err(error) => set_result.set(vec![format!("Invalid range: {error}")]),
+                },
                 _ => {
                     set_result.set(vec!["Invalid input(s).".to_string()]);
                 }
@@ -0,0 +1,496 @@
+//! Arbitrary-precision constants related to the Fibonacci numbers.
+//!
+//! Every constant is returned truncated toward zero to exactly the requested number of
+//! fractional digits, and every digit is guaranteed correct:
+//!
+//! - φ, 1/φ and √5 are enclosed between consecutive convergents F(m+1)/F(m) and F(m+2)/F(m+1),
+//!   which lie on opposite sides of φ. When both bounds truncate to the same digits, so does φ.
+//! - The reciprocal Fibonacci constant Σ 1/F(n) is evaluated from a theta-function series in
+//!   fixed point, tracking a bound on the rounding error of every operation and on the dropped
+//!   tail, which brackets the constant.
+//!
+//! In both cases the working precision is raised until the bounds agree.
+//!
+//! # Examples
+//!
+//! ```
+//! use fib_rs::constants;
+//!
+//! assert_eq!(constants::golden_ratio(20, 10).to_string(), "1.61803398874989484820");
+//! assert_eq!(constants::sqrt5(10, 2).to_string(), "10.0011110001");
+//! ```
+
+use std::fmt;
+
+use num_bigint::BigUint;
+use num_traits::{One, Zero};
+use rayon::prelude::*;
+
+use crate::{Fib, mul};
+
+/// Extra convergent indices added whenever the bounds for φ do not yet agree.
+const PHI_INDEX_STEP: u128 = 32;
+
+/// Extra working bits added whenever the bounds for the reciprocal Fibonacci constant do not
+/// yet agree.
+const RECIPROCAL_GUARD_STEP: u64 = 32;
+
+/// Units in the last place covering the terms dropped from the reciprocal series, each of which
+/// is below one unit.
+const RECIPROCAL_TAIL_UNITS: u64 = 32;
+
+/// A real constant truncated toward zero to a fixed number of digits after the radix point.
+///
+/// The value is stored as the integer floor(x * radix^digits).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Constant {
+    scaled: BigUint,
+    radix: u32,
+    digits: u64,
+}
+
+impl Constant {
+    /// The constant scaled by radix^digits and truncated, i.e. floor(x * radix^digits).
+    #[must_use]
+    pub fn scaled(&self) -> &BigUint {
+        &self.scaled
+    }
+
+    /// The radix in which the digits are counted.
+    #[must_use]
+    pub fn radix(&self) -> u32 {
+        self.radix
+    }
+
+    /// The number of digits after the radix point.
+    #[must_use]
+    pub fn digits(&self) -> u64 {
+        self.digits
+    }
+}
+
+impl fmt::Display for Constant {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let raw = self.scaled.to_str_radix(self.radix);
+        let digits = usize::try_from(self.digits).map_err(|_| fmt::Error)?;
+        if digits == 0 {
+            return f.write_str(&raw);
+        }
+        // Pad so there is always at least one digit before the point. Format widths are limited
+        // to u16, so the zeros are added by hand
+        let padded = "0".repeat((digits + 1).saturating_sub(raw.len())) + &raw;
+        let (integer, fraction) = padded.split_at(padded.len() - digits);
+        write!(f, "{integer}.{fraction}")
+    }
+}
+
+/// Compute the golden ratio φ = (1 + √5) / 2.
+///
+/// # Arguments
+///
+/// * `digits` - The number of digits after the radix point
+/// * `radix` - The radix of the digits, between 2 and 36
+///
+/// # Returns
+///
+/// * φ truncated to `digits` fractional digits
+///
+/// # Panics
+///
+/// Panics if `radix` is not between 2 and 36 or `digits` exceeds `u32::MAX`.
+///
+/// # Examples
+///
+/// ```
+/// use fib_rs::constants;
+///
+/// assert_eq!(constants::golden_ratio(5, 10).to_string(), "1.61803");
+/// ```
+#[must_use]
+pub fn golden_ratio(digits: u64, radix: u32) -> Constant {
+    let scale = scale(digits, radix);
+    Constant {
+        scaled: floor_scaled_phi(&scale, 1),
+        radix,
+        digits,
+    }
+}
+
+/// Compute the inverse of the golden ratio 1/φ = φ - 1.
+///
+/// # Arguments
+///
+/// * `digits` - The number of digits after the radix point
+/// * `radix` - The radix of the digits, between 2 and 36
+///
+/// # Returns
+///
+/// * 1/φ truncated to `digits` fractional digits
+///
+/// # Panics
+///
+/// Panics if `radix` is not between 2 and 36 or `digits` exceeds `u32::MAX`.
+///
+/// # Examples
+///
+/// ```
+/// use fib_rs::constants;
+///
+/// assert_eq!(constants::inverse_golden_ratio(5, 10).to_string(), "0.61803");
+/// ```
+#[must_use]
+pub fn inverse_golden_ratio(digits: u64, radix: u32) -> Constant {
+    let scale = scale(digits, radix);
+    Constant {
+        scaled: floor_scaled_phi(&scale, 1) - &scale,
+        radix,
+        digits,
+    }
+}
+
+/// Compute the square root of five √5 = 2φ - 1.
+///
+/// # Arguments
+///
+/// * `digits` - The number of digits after the radix point
+/// * `radix` - The radix of the digits, between 2 and 36
+///
+/// # Returns
+///
+/// * √5 truncated to `digits` fractional digits
+///
+/// # Panics
+///
+/// Panics if `radix` is not between 2 and 36 or `digits` exceeds `u32::MAX`.
+///
+/// # Examples
+///
+/// ```
+/// use fib_rs::constants;
+///
+/// assert_eq!(constants::sqrt5(5, 10).to_string(), "2.23606");
+/// ```
+#[must_use]
+pub fn sqrt5(digits: u64, radix: u32) -> Constant {
+    let scale = scale(digits, radix);
+    Constant {
+        scaled: floor_scaled_phi(&scale, 2) - &scale,
+        radix,
+        digits,
+    }
+}
+
+/// Compute the reciprocal Fibonacci constant Σ 1/F(n) for n ≥ 1.
+///
+/// With x = 1/φ and y = x², Landau's theta-function identity for the odd terms and Clausen's
+/// acceleration of the Lambert series for the even terms give
+///
+/// Σ 1/F(n) = (2 - x)·T² + Σ r(m)·y^(m²) - Σ L(2m)/F(2m)·y^(2m²)
+///
+/// where T = Σ y^(n(n+1)) and r(m) is L(m)/F(m) for even m and 5F(m)/L(m) for odd m. Terms fall
+/// like φ^(-2m²), so d digits need only O(√d) of them. The powers of y are computed in fixed
+/// point at a precision shrinking with their size, and the rational weights are summed by binary
+/// splitting, so a few hundred thousand digits take seconds.
+///
+/// # Arguments
+///
+/// * `digits` - The number of digits after the radix point
+/// * `radix` - The radix of the digits, between 2 and 36
+///
+/// # Returns
+///
+/// * The reciprocal Fibonacci constant truncated to `digits` fractional digits
+///
+/// # Panics
+///
+/// Panics if `radix` is not between 2 and 36 or `digits` exceeds `u32::MAX`.
+///
+/// # Examples
+///
+/// ```
+/// use fib_rs::constants;
+///
+/// assert_eq!(constants::reciprocal_fibonacci(10, 10).to_string(), "3.3598856662");
+/// ```
+#[must_use]
+pub fn reciprocal_fibonacci(digits: u64, radix: u32) -> Constant {
+    let scale = scale(digits, radix);
+
+    // The rounding errors add up to about the number of bits, so its logarithm in guard bits
+    // usually suffices
+    let mut guard_bits = 64 + u64::from(scale.bits().max(1).ilog2());
+    loop {
+        let bits = scale.bits() + guard_bits;
+        let (sum, error) = scaled_reciprocal_sum(bits);
+        let lower = mul::mul(&(&sum - error), &scale) >> bits;
+        let upper = mul::mul(&(&sum + error), &scale) >> bits;
+        if lower == upper {
+            return Constant {
+                scaled: lower,
+                radix,
+                digits,
+            };
+        }
+        guard_bits += RECIPROCAL_GUARD_STEP;
+    }
+}
+
+/// Compute radix^digits, validating the radix.
+fn scale(digits: u64, radix: u32) -> BigUint {
+    assert!(
+        (2..=36).contains(&radix),
+        "radix must be between 2 and 36, got {radix}"
+    );
+    BigUint::from(radix).pow(u32::try_from(digits).expect("digit count fits in u32"))
+}
+
+/// Compute floor(multiplier * scale * φ) from bracketing Fibonacci convergents.
+fn floor_scaled_phi(scale: &BigUint, multiplier: u32) -> BigUint {
+    // |F(m+1)/F(m) - φ| < 1/F(m)^2, so F(m) needs about half the bits of the scaled result
+    let mut m = u128::from(terms_for_bits(scale.bits() / 2 + 8)) | 1;
+    loop {
+        // For odd m, F(m+1)/F(m) < φ < F(m+2)/F(m+1)
+        let (fm, fm1) = Fib::fib_fast_doubling_helper(m);
+        let fm2 = &fm + &fm1;
+        let numerator = scale * multiplier;
+        let lower = &numerator * &fm1 / &fm;
+        let upper = &numerator * &fm2 / &fm1;
+        if lower == upper {
+            return lower;
+        }
+        m += PHI_INDEX_STEP;
+    }
+}
+
+/// Smallest index whose Fibonacci number certainly has more than `bits` bits.
+fn terms_for_bits(bits: u64) -> u64 {
+    // F(n) ≥ φ^(n-2) and log2(φ) > 0.69
+    bits * 100 / 69 + 3
+}
+
+/// A fixed-point approximation of a real number at scale 2^bits, with a bound on its absolute
+/// error in units in the last place.
+type Approx = (BigUint, u64);
+
+/// Compute the reciprocal Fibonacci constant at scale 2^bits by the series of
+/// [`reciprocal_fibonacci`], along with a bound on its error in units in the last place.
+fn scaled_reciprocal_sum(bits: u64) -> Approx {
+    let one = BigUint::one() << bits;
+    // x = φ - 1 is truncated, so it is below one unit too low, and y = x² within four units
+    let x = floor_scaled_phi(&one, 1) - &one;
+    let y: Approx = (mul::mul(&x, &x) >> bits, 4);
+
+    // Every term with an exponent of y beyond bits / log2(φ²) is below one unit, and
+    // log2(φ²) > 1.388
+    let cutoff = (bits * 1_000).div_ceil(1_388);
+    let powers = quarter_square_powers(&y, bits, cutoff);
+
+    // The powers y^⌊k²/4⌋ hold y^(m²) at k = 2m and y^(n(n+1)) at k = 2n + 1
+    let fib = Fib::range(0, powers.len() as u128 + 1);
+    let lucas = |m: usize| &fib[m + 1] + &fib[m.saturating_sub(1)];
+    let squares: Vec<(usize, &Approx)> = (1..).zip(powers.iter().skip(2).step_by(2)).collect();
+    let pronic = powers.iter().skip(1).step_by(2);
+
+    // T = Σ y^(n(n+1)), below 1.2
+    let theta_sum: Approx = pronic.fold((BigUint::zero(), 0), |(sum, error), term| {
+        (sum + &term.0, error + term.1)
+    });
+
+    // (2 - x)·T² with 2 - x below 1.4 and within one unit, and T² below 1.44
+    let theta_square: Approx = (
+        mul::mul(&theta_sum.0, &theta_sum.0) >> bits,
+        3 * theta_sum.1 + 2,
+    );
+    let two_minus_x = (&one << 1u32) - &x;
+    let theta: Approx = (
+        mul::mul(&two_minus_x, &theta_square.0) >> bits,
+        2 * theta_square.1 + 4,
+    );
+
+    // Σ r(m)·y^(m²) and Σ L(2m)/F(2m)·y^(2m²), where every weight is at most 5
+    let lambert: Vec<(BigUint, BigUint, BigUint, u64)> = squares
+        .iter()
+        .map(|&(m, (value, error))| {
+            let (numerator, denominator) = if m % 2 == 0 {
+                (lucas(m), fib[m].clone())
+            } else {
+                (&fib[m] * 5u32, lucas(m))
+            };
+            (value.clone(), numerator, denominator, *error)
+        })
+        .collect();
+    // Squares whose exponent exceeds that of every computed power fall in the dropped tail
+    let last_exponent = powers.len() * powers.len() / 4;
+    let doubled: Vec<(BigUint, BigUint, BigUint, u64)> = squares
+        .par_iter()
+        .filter(|&&(m, _)| 2 * m * m < last_exponent)
+        .map(|&(m, (value, error))| {
+            let square = mul::mul(value, value) >> bits;
+            (square, lucas(2 * m), fib[2 * m].clone(), 2 * error + 2)
+        })
+        .collect();
+    let (positive, negative) = rayon::join(|| weighted_sum(&lambert), || weighted_sum(&doubled));
+
+    let error = theta.1 + positive.1 + negative.1 + RECIPROCAL_TAIL_UNITS;
+    (theta.0 + positive.0 - negative.0, error)
+}
+
+/// Compute y^⌊k²/4⌋ at scale 2^bits for every k whose exponent is below `cutoff`.
+///
+/// Consecutive exponents differ by ⌈k/2⌉, so each power is the previous one times a running
+/// power of y. That multiplier is only kept to the precision of the power it scales, which
+/// shrinks as the powers do.
+fn quarter_square_powers(y: &Approx, bits: u64, cutoff: u64) -> Vec<Approx> {
+    let mut powers: Vec<Approx> = vec![(BigUint::one() << bits, 0)];
+    // y^⌈k/2⌉ at scale 2^precision, with its error in units of 2^-precision
+    let mut precision = bits;
+    let mut multiplier: Approx = (BigUint::one() << bits, 0);
+    for k in 0u64.. {
+        if (k + 1) * (k + 1) / 4 >= cutoff {
+            break;
+        }
+        let (value, error) = powers.last().expect("powers start with y^0");
+
+        // The power is below 2^-(bits - target), so multiplier bits below 2^-target are worth
+        // under one unit of it
+        let target = value.bits().min(bits);
+        if target < precision {
+            multiplier = (multiplier.0 >> (precision - target), multiplier.1 + 1);
+            precision = target;
+        }
+        if k % 2 == 1 {
+            let y_truncated = &y.0 >> (bits - precision);
+            multiplier = (
+                mul::mul(&multiplier.0, &y_truncated) >> precision,
+                multiplier.1 + y.1 + 3,
+            );
+        }
+        let next = mul::mul(value, &multiplier.0) >> precision;
+        powers.push((next, error + multiplier.1 + 2));
+    }
+    powers
+}
+
+/// Compute floor(Σ value·p/q) over terms (value, p, q, error) by binary splitting, so only one
+/// long division remains, along with a bound on its error given that p/q is at most 5.
+fn weighted_sum(terms: &[(BigUint, BigUint, BigUint, u64)]) -> Approx {
+    fn split(terms: &[(BigUint, BigUint, BigUint, u64)]) -> (BigUint, BigUint) {
+        match terms {
+            [] => (BigUint::zero(), BigUint::one()),
+            [(value, p, q, _)] => (mul::mul(value, p), q.clone()),
+            _ => {
+                let (left, right) = terms.split_at(terms.len() / 2);
+                let ((ln, lq), (rn, rq)) = rayon::join(|| split(left), || split(right));
+                (mul::mul(&ln, &rq) + mul::mul(&rn, &lq), mul::mul(&lq, &rq))
+            }
+        }
+    }
+    let (numerator, denominator) = split(terms);
+    let error = terms.iter().map(|term| 5 * term.3).sum::<u64>() + 1;
+    (numerator / denominator, error)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decimal_digits() {
+        assert_eq!(
+            golden_ratio(50, 10).to_string(),
+            "1.61803398874989484820458683436563811772030917980576"
+        );
+        assert_eq!(
+            inverse_golden_ratio(30, 10).to_string(),
+            "0.618033988749894848204586834365"
+        );
+        assert_eq!(
+            sqrt5(50, 10).to_string(),
+            "2.23606797749978969640917366873127623544061835961152"
+        );
+        assert_eq!(
+            reciprocal_fibonacci(40, 10).to_string(),
+            "3.3598856662431775531720113029189271796889"
+        );
+        assert_eq!(golden_ratio(0, 10).to_string(), "1");
+    }
+
+    /// Bracket Σ 1/F(n) by summing floor(2^bits / F(n)) directly, with one unit of error per
+    /// term and under one unit for the tail.
+    fn direct_reciprocal_fibonacci(digits: u32) -> BigUint {
+        let scale = BigUint::from(10u32).pow(digits);
+        let bits = scale.bits() + 64;
+        let one = BigUint::one() << bits;
+        let terms = terms_for_bits(bits + 3);
+        let (mut a, mut b) = (BigUint::one(), BigUint::one());
+        let mut lower = BigUint::zero();
+        for _ in 0..terms {
+            lower += &one / &a;
+            let next = &a + &b;
+            a = std::mem::replace(&mut b, next);
+        }
+        let upper = &lower + terms + 1u32;
+        let digits = (&lower * &scale) >> bits;
+        assert_eq!(digits, (upper * &scale) >> bits);
+        digits
+    }
+
+    #[test]
+    fn reciprocal_fibonacci_matches_direct_sum() {
+        for digits in [1, 2, 7, 64, 500, 2_000] {
+            assert_eq!(
+                *reciprocal_fibonacci(u64::from(digits), 10).scaled(),
+                direct_reciprocal_fibonacci(digits),
+                "{digits} digits"
+            );
+        }
+    }
+
+    #[test]
+    fn reciprocal_fibonacci_to_hundreds_of_thousands_of_digits() {
+        let digits = 100_000;
+        let decimal = reciprocal_fibonacci(digits, 10);
+        assert_eq!(decimal.to_string().len(), 100_002);
+
+        // The leading digits agree with the direct sum
+        let reference = direct_reciprocal_fibonacci(2_000);
+        assert_eq!(
+            decimal.scaled() / BigUint::from(10u32).pow(98_000),
+            reference
+        );
+
+        // And a binary expansion of the same length, computed at a different precision,
+        // brackets the decimal one
+        let bits = 332_200;
+        let binary = reciprocal_fibonacci(bits, 2);
+        let scale = BigUint::from(10u32).pow(100_000);
+        let lower = (binary.scaled() * &scale) >> bits;
+        let upper = ((binary.scaled() + 1u32) * &scale) >> bits;
+        assert!(lower <= *decimal.scaled() && *decimal.scaled() <= upper);
+        assert_eq!(lower, upper);
+    }
+
+    #[test]
+    fn other_radixes() {
+        assert_eq!(golden_ratio(12, 2).to_string(), "1.100111100011");
+        assert_eq!(golden_ratio(8, 16).to_string(), "1.9e3779b9");
+        assert_eq!(inverse_golden_ratio(8, 16).to_string(), "0.9e3779b9");
+    }
+
+    #[test]
+    fn long_expansions_are_consistent() {
+        // Truncating a longer expansion must give the shorter one
+        let long = sqrt5(3_000, 10);
+        let short = sqrt5(2_000, 10);
+        assert_eq!(
+            long.scaled() / BigUint::from(10u32).pow(1_000),
+            *short.scaled()
+        );
+
+        // And √5 squared must bracket 5
+        let s = long.scaled();
+        let five = BigUint::from(5u32) * BigUint::from(10u32).pow(6_000);
+        assert!(s * s <= five);
+        assert!((s + 1u32) * (s + 1u32) > five);
+    }
+}
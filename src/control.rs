@@ -0,0 +1,232 @@
+//! Cancellation, deadlines and progress reporting for long computations.
+//!
+//! A [`Control`] is passed to [`Fib::single_with`] or [`Fib::range_with`] and is consulted
+//! between units of work: after every doubling step of a single computation, and periodically
+//! while a range chunk is generated. A single big multiplication is never interrupted, so
+//! cancellation takes effect within one doubling step.
+//!
+//! [`Fib::single_with`]: crate::Fib::single_with
+//! [`Fib::range_with`]: crate::Fib::range_with
+
+use std::{
+    sync::{
+        Arc,
+        atomic::{AtomicBool, Ordering},
+    },
+    time::{Duration, Instant},
+};
+
+use crate::FibError;
+
+/// A shared flag used to request cancellation of a running computation.
+///
+/// Clones share the same flag, so one clone can be handed to the computation and another kept
+/// by whoever decides to stop it, possibly on another thread.
+///
+/// # Examples
+///
+/// ```
+/// use fib_rs::{CancellationToken, Control, Fib, FibError};
+///
+/// let token = CancellationToken::new();
+/// token.cancel();
+/// let control = Control::new().cancel_with(token);
+/// assert_eq!(Fib::single_with(1_000, &control), Err(FibError::Cancelled));
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct CancellationToken(Arc<AtomicBool>);
+
+impl CancellationToken {
+    /// Create a token that has not been cancelled.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Request cancellation of every computation observing this token.
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+
+    /// Whether cancellation has been requested.
+    #[must_use]
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+/// Progress of a running computation.
+///
+/// For [`Fib::single_with`] the units are doubling steps, one per bit of the index; for
+/// [`Fib::range_with`] they are completed chunks.
+///
+/// [`Fib::single_with`]: crate::Fib::single_with
+/// [`Fib::range_with`]: crate::Fib::range_with
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Progress {
+    /// Units of work completed so far
+    pub completed: u64,
+    /// Total units of work
+    pub total: u64,
+}
+
+/// Cancellation token, deadline and progress callback observed by a computation.
+///
+/// All parts are optional; `Control::new()` places no restrictions and reports nothing.
+///
+/// # Examples
+///
+/// ```
+/// use std::sync::atomic::{AtomicU64, Ordering};
+/// use std::time::Duration;
+/// use fib_rs::{Control, Fib, Progress};
+///
+/// let steps = AtomicU64::new(0);
+/// let report = |progress: Progress| steps.store(progress.completed, Ordering::Relaxed);
+/// let control = Control::new()
+///     .timeout(Duration::from_secs(60))
+///     .on_progress(&report);
+///
+/// let value = Fib::single_with(1_000, &control).unwrap();
+/// assert_eq!(value, Fib::single(1_000));
+/// assert_eq!(steps.load(Ordering::Relaxed), 10); // one doubling step per bit of 1000
+/// ```
+#[derive(Clone, Default)]
+pub struct Control<'a> {
+    token: Option<CancellationToken>,
+    deadline: Option<Instant>,
+    progress: Option<&'a (dyn Fn(Progress) + Sync)>,
+}
+
+impl<'a> Control<'a> {
+    /// Create a control with no token, deadline or progress callback.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Stop with `FibError::Cancelled` once `token` is cancelled.
+    #[must_use]
+    pub fn cancel_with(mut self, token: CancellationToken) -> Self {
+        self.token = Some(token);
+        self
+    }
+
+    /// Stop with `FibError::DeadlineExceeded` once `deadline` has passed.
+    #[must_use]
+    pub fn deadline(mut self, deadline: Instant) -> Self {
+        self.deadline = Some(deadline);
+        self
+    }
+
+    /// Stop with `FibError::DeadlineExceeded` once `timeout` has elapsed from now.
+    ///
+    /// # Panics
+    ///
+    /// Panics on targets without a clock, such as `wasm32-unknown-unknown`.
+    #[must_use]
+    pub fn timeout(self, timeout: Duration) -> Self {
+        self.deadline(Instant::now() + timeout)
+    }
+
+    /// Call `progress` whenever a unit of work completes.
+    ///
+    /// The callback may be invoked concurrently from several worker threads.
+    #[must_use]
+    pub fn on_progress(mut self, progress: &'a (dyn Fn(Progress) + Sync)) -> Self {
+        self.progress = Some(progress);
+        self
+    }
+
+    /// Whether any cancellation condition is configured.
+    pub(crate) fn is_interruptible(&self) -> bool {
+        self.token.is_some() || self.deadline.is_some()
+    }
+
+    /// Return an error if the computation should stop.
+    pub(crate) fn check(&self) -> Result<(), FibError> {
+        if self
+            .token
+            .as_ref()
+            .is_some_and(CancellationToken::is_cancelled)
+        {
+            return Err(FibError::Cancelled);
+        }
+        if self
+            .deadline
+            .is_some_and(|deadline| Instant::now() >= deadline)
+        {
+            return Err(FibError::DeadlineExceeded);
+        }
+        Ok(())
+    }
+
+    /// Report progress to the callback, if any.
+    pub(crate) fn report(&self, completed: u64, total: u64) {
+        if let Some(progress) = self.progress {
+            progress(Progress { completed, total });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn checks() {
+        assert_eq!(Control::new().check(), Ok(()));
+        assert!(!Control::new().is_interruptible());
+
+        let token = CancellationToken::new();
+        let control = Control::new().cancel_with(token.clone());
+        assert_eq!(control.check(), Ok(()));
+        token.cancel();
+        assert_eq!(control.check(), Err(FibError::Cancelled));
+
+        let expired = Control::new().deadline(Instant::now());
+        assert_eq!(expired.check(), Err(FibError::DeadlineExceeded));
+        let later = Control::new().timeout(Duration::from_hours(1));
+        assert_eq!(later.check(), Ok(()));
+    }
+
+    #[test]
+    fn cancellation_and_progress() {
+        use crate::Fib;
+        use std::sync::Mutex;
+
+        // Cancel from the progress callback after a few doubling steps
+        let token = CancellationToken::new();
+        let steps = Mutex::new(Vec::new());
+        let report = |progress: Progress| {
+            steps.lock().unwrap().push(progress);
+            if progress.completed == 3 {
+                token.cancel();
+            }
+        };
+        let control = Control::new()
+            .cancel_with(token.clone())
+            .on_progress(&report);
+        assert_eq!(
+            Fib::single_with(1 << 20, &control),
+            Err(FibError::Cancelled)
+        );
+        let steps = steps.into_inner().unwrap();
+        assert_eq!(steps.len(), 3);
+        assert!(steps.iter().all(|p| p.total == 21));
+
+        // Every range chunk reports once, and the last report covers all of them
+        let reports = Mutex::new(Vec::new());
+        let report = |progress: Progress| reports.lock().unwrap().push(progress);
+        let control = Control::new().on_progress(&report);
+        assert_eq!(
+            Fib::range_with(0, 5_000, &control),
+            Ok(Fib::range(0, 5_000))
+        );
+        let mut reports = reports.into_inner().unwrap();
+        reports.sort_by_key(|p| p.completed);
+        let total = reports[0].total;
+        assert_eq!(reports.len() as u64, total);
+        assert_eq!(reports.last().unwrap().completed, total);
+    }
+}
@@ -0,0 +1,332 @@
+//! Subquadratic decimal conversion for huge integers.
+//!
+//! `BigUint`'s `Display` divides by 10^19 once per output limb, which is quadratic and for
+//! F(10^7) and above takes longer than computing the number. Here the value is instead split in
+//! halves by powers 10^(k·2^i) built by repeated squaring. Each division is a Barrett reduction
+//! against a reciprocal obtained by Newton iteration, so it costs a few multiplications, and those
+//! run on the crate's Karatsuba and NTT products. Independent halves are formatted in parallel.
+
+use std::io::{self, Write};
+
+use num_bigint::BigUint;
+use num_traits::One;
+
+use crate::mul;
+
+/// Digits formatted by `BigUint`'s own conversion at the leaves of the recursion.
+const LEAF_DIGITS: usize = 256;
+
+/// Values below this many bits are converted directly, where splitting does not pay off.
+const DIRECT_BITS: u64 = 1 << 19;
+
+/// Divisors below this many bits get their reciprocal by long division rather than Newton
+/// iteration.
+const NEWTON_BITS: u64 = 1 << 12;
+
+/// Extra bits of precision carried into each Newton step, so one step suffices.
+const NEWTON_GUARD_BITS: u64 = 32;
+
+/// Halves with at least this many digits are formatted in parallel.
+const PARALLEL_DIGITS: usize = 1 << 14;
+
+/// Padded pieces with at most this many digits are formatted in one buffer when streaming.
+const STREAM_CHUNK_DIGITS: usize = 1 << 22;
+
+/// Convert a natural number to its decimal representation.
+///
+/// Produces the same string as `value.to_string()`, in O(M(n) log n) time for an n-bit value
+/// rather than O(n^2), with independent parts converted in parallel.
+///
+/// # Arguments
+///
+/// * `value` - The number to convert
+///
+/// # Returns
+///
+/// * The decimal digits of `value`, without leading zeros
+///
+/// # Examples
+///
+/// ```
+/// use fib_rs::{Fib, to_decimal};
+///
+/// let value = Fib::single(100_000);
+/// assert_eq!(to_decimal(&value), value.to_string());
+/// ```
+#[must_use]
+#[allow(clippy::missing_panics_doc)] // Only ASCII digits are ever pushed
+pub fn to_decimal(value: &BigUint) -> String {
+    let mut digits = Vec::new();
+    Converter::new(value, DIRECT_BITS).push(value, &mut digits);
+    String::from_utf8(digits).expect("decimal digits are ASCII")
+}
+
+/// Write the decimal representation of a natural number to `writer` without building the whole
+/// string.
+///
+/// Digits are produced most significant first in pieces of bounded size, each converted in
+/// parallel and written as soon as it is complete.
+///
+/// # Arguments
+///
+/// * `value` - The number to convert
+/// * `writer` - The destination of the digits
+///
+/// # Errors
+///
+/// * Any error returned by `writer`
+///
+/// # Examples
+///
+/// ```
+/// use fib_rs::{Fib, write_decimal};
+///
+/// let value = Fib::single(100_000);
+/// let mut out = Vec::new();
+/// write_decimal(&value, &mut out).unwrap();
+/// assert_eq!(out, value.to_string().into_bytes());
+/// ```
+pub fn write_decimal<W: Write>(value: &BigUint, mut writer: W) -> io::Result<()> {
+    Converter::new(value, DIRECT_BITS).write(value, &mut writer)
+}
+
+/// The powers 10^(`LEAF_DIGITS`·2^i) up to about the square root of a value, with the reciprocals
+/// used to divide by them.
+struct Converter {
+    /// The ith power, with `LEAF_DIGITS << i` zeros.
+    powers: Vec<BigUint>,
+    /// floor(2^(2s) / power) for the bit length s of each power.
+    reciprocals: Vec<BigUint>,
+    /// Padded pieces with at most this many digits are formatted in one buffer when streaming.
+    stream_digits: usize,
+}
+
+impl Converter {
+    /// Build every power not above `value`, or none if `value` has fewer than `direct_bits` bits.
+    fn new(value: &BigUint, direct_bits: u64) -> Self {
+        let mut powers = Vec::new();
+        if value.bits() >= direct_bits {
+            let exponent = u32::try_from(LEAF_DIGITS).expect("leaf digits fit in u32");
+            let mut power = BigUint::from(10u32).pow(exponent);
+            while power <= *value {
+                let next = mul::mul(&power, &power);
+                powers.push(power);
+                power = next;
+            }
+        }
+        // Each power is the square of the previous one, so the square of the previous reciprocal
+        // is an estimate of the next correct to about half of its bits
+        let mut reciprocals: Vec<BigUint> = Vec::with_capacity(powers.len());
+        for (level, power) in powers.iter().enumerate() {
+            let next = match reciprocals.last() {
+                Some(previous) => {
+                    let shift = 4 * powers[level - 1].bits() - 2 * power.bits();
+                    refine(power, mul::mul(previous, previous) >> shift)
+                }
+                None => reciprocal(power),
+            };
+            reciprocals.push(next);
+        }
+        Self {
+            powers,
+            reciprocals,
+            stream_digits: STREAM_CHUNK_DIGITS,
+        }
+    }
+
+    /// Split `value` into the quotient and remainder by the power at `level`.
+    ///
+    /// `value` must be below the square of that power.
+    fn div_rem(&self, value: &BigUint, level: usize) -> (BigUint, BigUint) {
+        let divisor = &self.powers[level];
+        let bits = divisor.bits();
+        // Barrett reduction: the estimate is at most two below the true quotient
+        let mut quotient = mul::mul(&(value >> (bits - 1)), &self.reciprocals[level]) >> (bits + 1);
+        let mut remainder = value - mul::mul(&quotient, divisor);
+        while remainder >= *divisor {
+            remainder -= divisor;
+            quotient += 1u32;
+        }
+        (quotient, remainder)
+    }
+
+    /// The highest level whose power is not above `value`, if any.
+    fn top_level(&self, value: &BigUint) -> Option<usize> {
+        self.powers.iter().rposition(|power| power <= value)
+    }
+
+    /// Append the digits of `value` to `out`, without leading zeros.
+    fn push(&self, value: &BigUint, out: &mut Vec<u8>) {
+        let Some(level) = self.top_level(value) else {
+            out.extend_from_slice(value.to_string().as_bytes());
+            return;
+        };
+        let (high, low) = self.div_rem(value, level);
+        self.push(&high, out);
+        let at = out.len();
+        out.resize(at + (LEAF_DIGITS << level), b'0');
+        self.fill(&low, level, &mut out[at..]);
+    }
+
+    /// Write the digits of `value` to `writer`, without leading zeros.
+    fn write(&self, value: &BigUint, writer: &mut impl Write) -> io::Result<()> {
+        let Some(level) = self.top_level(value) else {
+            return writer.write_all(value.to_string().as_bytes());
+        };
+        let (high, low) = self.div_rem(value, level);
+        self.write(&high, writer)?;
+        self.write_padded(&low, level, writer)
+    }
+
+    /// Write `value`, which is below the power at `level`, padded with zeros to the width of
+    /// that power.
+    fn write_padded(
+        &self,
+        value: &BigUint,
+        level: usize,
+        writer: &mut impl Write,
+    ) -> io::Result<()> {
+        let width = LEAF_DIGITS << level;
+        if width <= self.stream_digits {
+            let mut digits = vec![b'0'; width];
+            self.fill(value, level, &mut digits);
+            return writer.write_all(&digits);
+        }
+        let (high, low) = self.div_rem(value, level - 1);
+        self.write_padded(&high, level - 1, writer)?;
+        self.write_padded(&low, level - 1, writer)
+    }
+
+    /// Write the digits of `value`, which is below the power at `level`, right-aligned into
+    /// `out`, whose length is the width of that power and which is filled with zeros.
+    fn fill(&self, value: &BigUint, level: usize, out: &mut [u8]) {
+        if level == 0 {
+            let digits = value.to_string();
+            let at = out.len() - digits.len();
+            out[at..].copy_from_slice(digits.as_bytes());
+            return;
+        }
+        let (high, low) = self.div_rem(value, level - 1);
+        let parallel = out.len() >= PARALLEL_DIGITS;
+        let (high_out, low_out) = out.split_at_mut(out.len() / 2);
+        if parallel {
+            rayon::join(
+                || self.fill(&high, level - 1, high_out),
+                || self.fill(&low, level - 1, low_out),
+            );
+        } else {
+            self.fill(&high, level - 1, high_out);
+            self.fill(&low, level - 1, low_out);
+        }
+    }
+}
+
+/// Compute floor(2^(2s) / divisor) for the bit length s of a nonzero divisor.
+fn reciprocal(divisor: &BigUint) -> BigUint {
+    let bits = divisor.bits();
+    let scale = BigUint::one() << (2 * bits);
+    if bits <= NEWTON_BITS {
+        return scale / divisor;
+    }
+
+    // Start from the reciprocal of the top half of the divisor, scaled up, which is correct to
+    // about half of the bits
+    let shift = bits - (bits / 2 + NEWTON_GUARD_BITS);
+    refine(divisor, reciprocal(&(divisor >> shift)) << shift)
+}
+
+/// Compute floor(2^(2s) / divisor) for the bit length s of a nonzero divisor from an estimate
+/// correct to about half of its bits.
+fn refine(divisor: &BigUint, estimate: BigUint) -> BigUint {
+    let bits = divisor.bits();
+    let scale = BigUint::one() << (2 * bits);
+
+    // One Newton step y += y·(2^(2s) − d·y) / 2^(2s) doubles the correct bits
+    let product = mul::mul(divisor, &estimate);
+    let mut result = if product <= scale {
+        let correction = mul::mul(&estimate, &(&scale - &product)) >> (2 * bits);
+        estimate + correction
+    } else {
+        let correction = mul::mul(&estimate, &(&product - &scale)) >> (2 * bits);
+        estimate - correction - 1u32
+    };
+
+    // The step leaves an error of a few units at most
+    let mut product = mul::mul(divisor, &result);
+    while product > scale {
+        result -= 1u32;
+        product -= divisor;
+    }
+    while &scale - &product >= *divisor {
+        result += 1u32;
+        product += divisor;
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Fib;
+
+    #[test]
+    fn reciprocals_are_exact() {
+        for bits in [1u32, 64, 4_095, 4_097, 10_000, 70_001] {
+            for divisor in [
+                (BigUint::one() << bits) - 1u32,
+                BigUint::one() << (bits - 1),
+                Fib::single(u128::from(bits) * 3 / 2).max(BigUint::one()),
+            ] {
+                let scale = BigUint::one() << (2 * divisor.bits());
+                assert_eq!(reciprocal(&divisor), &scale / &divisor, "{bits} bits");
+            }
+        }
+    }
+
+    /// Convert through the recursion whatever the size of `value`.
+    fn split_decimal(value: &BigUint) -> (String, Vec<u8>) {
+        let powers = Converter::new(value, 0);
+        let mut digits = Vec::new();
+        powers.push(value, &mut digits);
+        let mut written = Vec::new();
+        powers.write(value, &mut written).unwrap();
+        (String::from_utf8(digits).unwrap(), written)
+    }
+
+    #[test]
+    fn conversions_match_display() {
+        let pool = rayon::ThreadPoolBuilder::new()
+            .num_threads(4)
+            .build()
+            .unwrap();
+        pool.install(|| {
+            let values = [
+                BigUint::ZERO,
+                BigUint::from(7u32),
+                Fib::single(300_000),
+                // Runs of zeros across every split
+                BigUint::from(10u32).pow(256 * 40),
+                BigUint::from(10u32).pow(256 * 33) - 1u32,
+                BigUint::from(10u32).pow(50_000) + 1u32,
+            ];
+            for value in values {
+                let expected = value.to_string();
+                let (digits, written) = split_decimal(&value);
+                assert_eq!(digits, expected);
+                assert_eq!(written, expected.as_bytes());
+                assert_eq!(to_decimal(&value), expected);
+            }
+        });
+    }
+
+    #[test]
+    fn streaming_splits_large_pieces() {
+        // Pieces wider than the stream chunk are split before they are formatted
+        let value = Fib::single(100_000);
+        let mut powers = Converter::new(&value, 0);
+        powers.stream_digits = LEAF_DIGITS;
+        let mut written = Vec::new();
+        powers.write(&value, &mut written).unwrap();
+        assert_eq!(written, value.to_string().into_bytes());
+    }
+}
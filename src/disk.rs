@@ -0,0 +1,951 @@
+//! Out-of-core computation of results larger than memory.
+//!
+//! [`Fib::single_to_file`] runs the fast doubling steps on operands kept in scratch files as
+//! 64-bit limbs, least significant first. The leading steps, while F(k) still fits in a block, run
+//! in memory. Every later step needs the transforms of F(k) and F(k+1) only:
+//!
+//! - F(2k+1) = F(k)² + F(k+1)² and F(2k+2) = F(k+1)(F(k+1) + 2F(k)) are formed pointwise from
+//!   them and transformed back, and F(2k) is their difference.
+//! - The transforms are the number-theoretic transforms of [`crate::ntt`], of length
+//!   N = rows · columns, done in four steps over a file of residue triples in row-major order.
+//!   Column passes read panels of a few columns of every row, transform each column and apply
+//!   the twiddle factors; the row pass transforms whole rows, so each element is read and
+//!   written a constant number of times per transform and a step takes O(N log N) time.
+//! - The coefficients recovered by the Chinese remainder theorem are carried in one sequential
+//!   pass.
+//!
+//! For decimal output the limbs hold 19 decimal digits each instead of 64 bits. The transforms
+//! multiply in any radix, so the digits are written in a single pass over the value.
+
+use std::{
+    fmt::Write as _,
+    fs::{self, File, OpenOptions},
+    io::{self, BufReader, BufWriter, Read, Seek, SeekFrom, Write},
+    path::{Path, PathBuf},
+    process,
+    sync::atomic::{AtomicU64, Ordering},
+};
+
+use num_bigint::BigUint;
+use rayon::prelude::*;
+
+use crate::{
+    Fib, FibError, decimal,
+    estimate::LOG2_PHI_UPPER,
+    ntt::{Garner, PRIMES, Prime},
+};
+
+/// Default working memory, 1 GiB.
+const DEFAULT_MEMORY_LIMIT: u64 = 1 << 30;
+
+/// Bytes of working memory per byte of a block: the in-memory doubling steps that produce a
+/// block-sized F(k) hold a few values of twice its size along with the temporaries of their
+/// products.
+const DISK_MEMORY_FACTOR: u64 = 16;
+
+/// Smallest block, in limbs, whatever the memory limit.
+const MIN_BLOCK_LIMBS: u64 = 16;
+
+/// Bytes per element of a transform file: the residues modulo the three NTT primes.
+const ELEMENT_BYTES: usize = 24;
+
+/// Bytes of working memory per byte of transform elements held by a pass: the elements, their
+/// copies per prime and the buffers of the file I/O.
+const TRANSFORM_MEMORY_FACTOR: u64 = 4;
+
+/// Fewest transform elements held by a pass, whatever the memory limit.
+const MIN_TRANSFORM_ELEMENTS: u64 = 64;
+
+/// Decimal digits per limb in decimal radix.
+const DECIMAL_DIGITS: usize = 19;
+
+/// The radix of limbs in decimal radix, 10^19.
+const DECIMAL_BASE: u64 = 10_000_000_000_000_000_000;
+
+/// Counter making scratch file names unique within the process.
+static NEXT_FILE: AtomicU64 = AtomicU64::new(0);
+
+/// Configuration of the out-of-core mode: where scratch files go and how much memory to use.
+///
+/// # Examples
+///
+/// ```
+/// use fib_rs::OutOfCore;
+///
+/// let config = OutOfCore::new("/tmp").memory_limit(4 << 30);
+/// assert_eq!(config.memory_limit_bytes(), 4 << 30);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OutOfCore {
+    scratch_dir: PathBuf,
+    memory_limit: u64,
+}
+
+/// Encoding of the value written by [`Fib::single_to_file`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum FileFormat {
+    /// Decimal digits without a trailing newline
+    #[default]
+    Decimal,
+    /// 64-bit limbs, least significant first, each in little-endian byte order
+    Binary,
+}
+
+impl OutOfCore {
+    /// Keep scratch files in `scratch_dir`, which is created if missing, with 1 GiB of working
+    /// memory.
+    #[must_use]
+    pub fn new(scratch_dir: impl Into<PathBuf>) -> Self {
+        Self {
+            scratch_dir: scratch_dir.into(),
+            memory_limit: DEFAULT_MEMORY_LIMIT,
+        }
+    }
+
+    /// Use about `limit_bytes` of working memory. Scratch files take the rest of the space.
+    #[must_use]
+    pub fn memory_limit(mut self, limit_bytes: u64) -> Self {
+        self.memory_limit = limit_bytes;
+        self
+    }
+
+    /// The directory that holds scratch files.
+    #[must_use]
+    pub fn scratch_dir(&self) -> &Path {
+        &self.scratch_dir
+    }
+
+    /// The working memory limit, in bytes.
+    #[must_use]
+    pub fn memory_limit_bytes(&self) -> u64 {
+        self.memory_limit
+    }
+
+    /// Number of limbs processed in memory at a time.
+    fn block_limbs(&self) -> u64 {
+        (self.memory_limit / 8 / DISK_MEMORY_FACTOR).max(MIN_BLOCK_LIMBS)
+    }
+}
+
+impl Fib {
+    /// Calculate the nth Fibonacci number and write it to a file, keeping operands on disk once
+    /// they outgrow the memory limit.
+    ///
+    /// For results of tens of gigabytes. Each doubling step on disk transforms its operands with
+    /// a four-step number-theoretic transform over scratch files, so it takes O(N log N) time in
+    /// the size N of its result and reads and writes each byte of its scratch files a few times;
+    /// the whole computation costs about two such steps at the size of the result. On a single
+    /// core, F(1.15 · 10^10), a 1 GB result, took 8.5 minutes with the default 1 GiB of memory,
+    /// so a 10 GB result takes about an hour and a half, and less with more cores for as long as
+    /// the disk keeps up. Scratch files peak at 7 to 14 times the size of the binary result,
+    /// depending on how far the transform length is rounded up to a power of two, and are
+    /// removed when the call returns.
+    ///
+    /// # Arguments
+    ///
+    /// * `n` - The index of the Fibonacci number to calculate
+    /// * `path` - The file to write the value to, replacing any existing file
+    /// * `format` - The encoding of the value
+    /// * `config` - The scratch directory and memory limit
+    ///
+    /// # Errors
+    ///
+    /// * `FibError::Io` if the scratch directory or a scratch file cannot be created, read or
+    ///   written, or if the output cannot be written
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use fib_rs::{Fib, FileFormat, OutOfCore};
+    ///
+    /// let dir = std::env::temp_dir().join("fib-rs-doc");
+    /// let path = dir.join("f1000.txt");
+    /// Fib::single_to_file(1_000, &path, FileFormat::Decimal, &OutOfCore::new(&dir)).unwrap();
+    /// assert_eq!(std::fs::read_to_string(&path).unwrap(), Fib::single(1_000).to_string());
+    /// # std::fs::remove_dir_all(dir).unwrap();
+    /// ```
+    pub fn single_to_file(
+        n: u128,
+        path: impl AsRef<Path>,
+        format: FileFormat,
+        config: &OutOfCore,
+    ) -> Result<(), FibError> {
+        fs::create_dir_all(&config.scratch_dir)?;
+        let scratch = Scratch {
+            dir: &config.scratch_dir,
+            block: config.block_limbs(),
+            memory: config.memory_limit,
+            radix: match format {
+                FileFormat::Decimal => Radix::Decimal,
+                FileFormat::Binary => Radix::Binary,
+            },
+        };
+        let value = scratch.single(n)?;
+        match format {
+            FileFormat::Binary => value.persist(path.as_ref())?,
+            FileFormat::Decimal => scratch.write_decimal(&value, path.as_ref())?,
+        }
+        Ok(())
+    }
+}
+
+/// The radix of the limbs of values on disk.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Radix {
+    /// Limbs of 64 bits
+    Binary,
+    /// Limbs of 19 decimal digits
+    Decimal,
+}
+
+impl Radix {
+    fn base(self) -> u128 {
+        match self {
+            Self::Binary => 1 << 64,
+            Self::Decimal => DECIMAL_BASE.into(),
+        }
+    }
+
+    /// The limbs of `value`, least significant first.
+    fn limbs(self, value: &BigUint) -> Vec<u64> {
+        match self {
+            Self::Binary => value.to_u64_digits(),
+            Self::Decimal if *value == BigUint::ZERO => Vec::new(),
+            Self::Decimal => {
+                let digits = decimal::to_decimal(value);
+                digits
+                    .as_bytes()
+                    .rchunks(DECIMAL_DIGITS)
+                    .map(|chunk| {
+                        chunk
+                            .iter()
+                            .fold(0, |limb, digit| limb * 10 + u64::from(digit - b'0'))
+                    })
+                    .collect()
+            }
+        }
+    }
+}
+
+/// A natural number stored in a scratch file, removed when dropped.
+struct LimbFile {
+    path: PathBuf,
+    /// Number of limbs, without leading zeros.
+    limbs: u64,
+}
+
+impl LimbFile {
+    /// Move the file to `path`, or copy it across file systems.
+    fn persist(self, path: &Path) -> io::Result<()> {
+        if fs::rename(&self.path, path).is_err() {
+            fs::copy(&self.path, path)?;
+        }
+        Ok(())
+    }
+}
+
+impl Drop for LimbFile {
+    fn drop(&mut self) {
+        // Already gone if it was persisted
+        let _ = fs::remove_file(&self.path);
+    }
+}
+
+/// Sequential writer of a [`LimbFile`] that drops leading zero limbs when finished.
+struct LimbWriter {
+    file: LimbFile,
+    writer: BufWriter<File>,
+    written: u64,
+}
+
+impl LimbWriter {
+    fn push(&mut self, limb: u64) -> io::Result<()> {
+        self.writer.write_all(&limb.to_le_bytes())?;
+        self.written += 1;
+        if limb != 0 {
+            self.file.limbs = self.written;
+        }
+        Ok(())
+    }
+
+    fn finish(self) -> io::Result<LimbFile> {
+        let file = self
+            .writer
+            .into_inner()
+            .map_err(io::IntoInnerError::into_error)?;
+        file.set_len(self.file.limbs * 8)?;
+        Ok(self.file)
+    }
+}
+
+/// Reader of the limbs of a [`LimbFile`] in order, yielding zeros past its end.
+struct LimbReader {
+    reader: BufReader<File>,
+    remaining: u64,
+}
+
+impl LimbReader {
+    fn new(file: &LimbFile) -> io::Result<Self> {
+        Ok(Self {
+            reader: BufReader::new(File::open(&file.path)?),
+            remaining: file.limbs,
+        })
+    }
+
+    fn next(&mut self) -> io::Result<u64> {
+        if self.remaining == 0 {
+            return Ok(0);
+        }
+        self.remaining -= 1;
+        let mut bytes = [0; 8];
+        self.reader.read_exact(&mut bytes)?;
+        Ok(u64::from_le_bytes(bytes))
+    }
+}
+
+/// A scratch file of transform elements, each the residues of one coefficient modulo the
+/// three NTT primes, removed when dropped.
+struct TransformFile {
+    path: PathBuf,
+    file: File,
+}
+
+impl Drop for TransformFile {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.path);
+    }
+}
+
+/// The shape of a transform of length rows · columns held in a file in row-major order, and
+/// how much of it each pass holds in memory.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Layout {
+    log_rows: u32,
+    log_columns: u32,
+    /// Columns read together by a column pass
+    panel: usize,
+    /// Rows read together by the row pass
+    batch: usize,
+}
+
+impl Layout {
+    /// Split a transform of length 2^`log_len` for passes within `memory` bytes.
+    ///
+    /// Rows are made as long as a quarter of the elements a pass may hold, so the column
+    /// passes read panels many elements wide from each row.
+    fn new(log_len: u32, memory: u64) -> Self {
+        let elements =
+            (memory / ELEMENT_BYTES as u64 / TRANSFORM_MEMORY_FACTOR).max(MIN_TRANSFORM_ELEMENTS);
+        let log_columns = log_len.min((elements / 4).ilog2());
+        let log_rows = log_len - log_columns;
+        let elements = usize::try_from(elements).unwrap_or(usize::MAX);
+        Self {
+            log_rows,
+            log_columns,
+            panel: (elements >> log_rows).clamp(1, 1 << log_columns),
+            // Each row of the batch is held for both operands
+            batch: (elements >> (log_columns + 1)).clamp(1, 1 << log_rows),
+        }
+    }
+
+    fn rows(self) -> usize {
+        1 << self.log_rows
+    }
+
+    fn columns(self) -> usize {
+        1 << self.log_columns
+    }
+
+    /// Byte offset of the element in `row` and `column`.
+    fn offset(self, row: usize, column: usize) -> u64 {
+        ((row * self.columns() + column) * ELEMENT_BYTES) as u64
+    }
+
+    /// Index of the frequency held at position `index` of a transformed column, which the
+    /// forward transform leaves in bit-reversed order.
+    fn frequency(self, index: usize) -> usize {
+        if self.log_rows == 0 {
+            0
+        } else {
+            index.reverse_bits() >> (usize::BITS - self.log_rows)
+        }
+    }
+}
+
+/// The constants of one NTT prime for a transform of a given [`Layout`].
+struct Twiddles {
+    prime: Prime,
+    /// Roots of unity for the column transforms
+    column_roots: Vec<u64>,
+    /// Roots of unity for the row transforms
+    row_roots: Vec<u64>,
+    /// A primitive root of unity of the full length, and its inverse, in Montgomery form
+    root: u64,
+    inverse_root: u64,
+    /// 2^128 / N in Montgomery form, scaling the pointwise products as in [`crate::ntt`]
+    scale: u64,
+    /// One in Montgomery form
+    one: u64,
+}
+
+impl Twiddles {
+    fn new(prime: Prime, layout: Layout) -> Self {
+        let log_len = layout.log_rows + layout.log_columns;
+        let root = prime.root_of_unity(log_len);
+        let n_inv = prime.p - ((prime.p - 1) >> log_len);
+        Self {
+            prime,
+            column_roots: prime.roots(layout.log_rows),
+            row_roots: prime.roots(layout.log_columns),
+            root,
+            inverse_root: prime.pow(root, (1 << log_len) - 1),
+            scale: prime.to_montgomery(prime.to_montgomery(n_inv)),
+            one: prime.to_montgomery(1),
+        }
+    }
+}
+
+/// The not yet emitted part of a carried sum of coefficients, as four 64-bit words from the
+/// least significant.
+#[derive(Debug, Default)]
+struct Carry([u64; 4]);
+
+impl Carry {
+    fn add(&mut self, value: [u64; 3]) {
+        let mut carry = false;
+        for (word, &addend) in self.0.iter_mut().zip(value.iter().chain(&[0])) {
+            let (sum, overflow) = word.overflowing_add(addend);
+            let (sum, carried) = sum.overflowing_add(u64::from(carry));
+            *word = sum;
+            carry = overflow || carried;
+        }
+    }
+
+    fn is_zero(&self) -> bool {
+        self.0 == [0; 4]
+    }
+
+    /// Remove and return the least significant limb in `radix`.
+    #[allow(clippy::cast_possible_truncation)] // Quotients and remainders fit in a word
+    fn take(&mut self, radix: Radix) -> u64 {
+        match radix {
+            Radix::Binary => {
+                let limb = self.0[0];
+                self.0 = [self.0[1], self.0[2], self.0[3], 0];
+                limb
+            }
+            Radix::Decimal => {
+                let mut remainder = 0u128;
+                for word in self.0.iter_mut().rev() {
+                    let current = (remainder << 64) | u128::from(*word);
+                    *word = (current / u128::from(DECIMAL_BASE)) as u64;
+                    remainder = current % u128::from(DECIMAL_BASE);
+                }
+                remainder as u64
+            }
+        }
+    }
+}
+
+/// The scratch directory, block size and radix of one out-of-core computation.
+struct Scratch<'a> {
+    dir: &'a Path,
+    /// Limbs per block, chosen from the memory limit.
+    block: u64,
+    /// The memory limit, in bytes.
+    memory: u64,
+    radix: Radix,
+}
+
+impl Scratch<'_> {
+    /// A fresh path in the scratch directory.
+    fn path(&self) -> PathBuf {
+        let id = NEXT_FILE.fetch_add(1, Ordering::Relaxed);
+        self.dir
+            .join(format!("fib-rs-{}-{id}.limbs", process::id()))
+    }
+
+    fn create(&self) -> io::Result<LimbWriter> {
+        let path = self.path();
+        let writer = BufWriter::new(File::create(&path)?);
+        Ok(LimbWriter {
+            file: LimbFile { path, limbs: 0 },
+            writer,
+            written: 0,
+        })
+    }
+
+    fn store(&self, value: &BigUint) -> io::Result<LimbFile> {
+        let mut writer = self.create()?;
+        for limb in self.radix.limbs(value) {
+            writer.push(limb)?;
+        }
+        writer.finish()
+    }
+
+    /// Compute `a - b`, which must not be negative.
+    #[allow(clippy::cast_possible_truncation)] // Differences are below the radix
+    fn sub(&self, a: &LimbFile, b: &LimbFile) -> io::Result<LimbFile> {
+        let base = self.radix.base();
+        let (mut a_reader, mut b_reader) = (LimbReader::new(a)?, LimbReader::new(b)?);
+        let mut writer = self.create()?;
+        let mut borrow = 0;
+        for _ in 0..a.limbs {
+            let (x, y) = (
+                u128::from(a_reader.next()?),
+                u128::from(b_reader.next()?) + borrow,
+            );
+            let (difference, next) = if x >= y {
+                (x - y, 0)
+            } else {
+                (x + base - y, 1)
+            };
+            writer.push(difference as u64)?;
+            borrow = next;
+        }
+        debug_assert_eq!(borrow, 0, "difference is not negative");
+        writer.finish()
+    }
+
+    /// Compute F(n) into a scratch file.
+    fn single(&self, n: u128) -> io::Result<LimbFile> {
+        // Run the leading doubling steps in memory while F(k) fits in a block
+        #[allow(clippy::cast_precision_loss)] // The prefix only needs to fit approximately
+        let fits = |k: u128| k as f64 * LOG2_PHI_UPPER <= (self.block * 64) as f64;
+        let mut shift = 0;
+        while !fits(n >> shift) {
+            shift += 1;
+        }
+        if shift == 0 {
+            return self.store(&Fib::single(n));
+        }
+        let (current, next) = Fib::fib_fast_doubling_helper(n >> shift);
+        let (mut current, mut next) = (self.store(&current)?, self.store(&next)?);
+
+        for bit in (0..shift).rev() {
+            let (odd, high) = self.double(current, next)?;
+            (current, next) = if (n >> bit) & 1 == 1 {
+                (odd, high)
+            } else {
+                (self.sub(&high, &odd)?, odd)
+            };
+        }
+        Ok(current)
+    }
+
+    /// From F(k) and F(k+1), compute F(2k+1) = F(k)² + F(k+1)² and
+    /// F(2k+2) = F(k+1)(F(k+1) + 2F(k)), transforming each operand once.
+    ///
+    /// Neither combination subtracts, so every coefficient of the limb convolutions is below
+    /// 3 · N · radix², within the range the three primes recover.
+    fn double(&self, current: LimbFile, next: LimbFile) -> io::Result<(LimbFile, LimbFile)> {
+        // F(k+1) is the longer operand, so the products have fewer than twice its limbs
+        let len = 2 * next.limbs - 1;
+        let layout = Layout::new(len.next_power_of_two().trailing_zeros(), self.memory);
+        let twiddles = PRIMES.map(|prime| Twiddles::new(prime, layout));
+
+        let low = self.forward_columns(&current, layout, &twiddles)?;
+        let high = self.forward_columns(&next, layout, &twiddles)?;
+        drop((current, next));
+        double_rows(&low, &high, layout, &twiddles)?;
+
+        let garner = Garner::new();
+        inverse_columns(&low, layout, &twiddles, &garner)?;
+        let odd = self.carry(&low, len)?;
+        drop(low);
+        inverse_columns(&high, layout, &twiddles, &garner)?;
+        Ok((odd, self.carry(&high, len)?))
+    }
+
+    /// First transform step: read the limbs of `input` panel by panel, transform the columns
+    /// modulo each prime and twist them, writing a new transform file.
+    fn forward_columns(
+        &self,
+        input: &LimbFile,
+        layout: Layout,
+        twiddles: &[Twiddles; 3],
+    ) -> io::Result<TransformFile> {
+        let (rows, columns) = (layout.rows(), layout.columns());
+        let path = self.path();
+        let file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(true)
+            .open(&path)?;
+        let transform = TransformFile { path, file };
+        transform.file.set_len(layout.offset(rows, 0))?;
+
+        let source = File::open(&input.path)?;
+        let mut bytes = Vec::new();
+        for start in (0..columns).step_by(layout.panel) {
+            let width = layout.panel.min(columns - start);
+            // Gather the panel column by column; rows past the end of the input are zero
+            let mut limbs = vec![0; rows * width];
+            for row in 0..rows {
+                let first = (row * columns + start) as u64;
+                let available = input.limbs.saturating_sub(first).min(width as u64);
+                if available == 0 {
+                    break;
+                }
+                bytes.resize(
+                    usize::try_from(available * 8).expect("panel fits in memory"),
+                    0,
+                );
+                read_at(&source, first * 8, &mut bytes)?;
+                for (j, limb) in bytes.chunks_exact(8).enumerate() {
+                    limbs[j * rows + row] = u64::from_le_bytes(limb.try_into().expect("8 bytes"));
+                }
+            }
+
+            let mut panel = vec![0; rows * width * 3];
+            panel
+                .par_chunks_mut(rows * 3)
+                .zip(limbs.par_chunks(rows))
+                .enumerate()
+                .for_each(|(j, (out, column))| {
+                    for (i, twiddles) in twiddles.iter().enumerate() {
+                        let prime = twiddles.prime;
+                        let mut values: Vec<u64> =
+                            column.iter().map(|&limb| limb % prime.p).collect();
+                        prime.forward(&mut values, &twiddles.column_roots);
+                        // Frequency k of column c is multiplied by w^(k c) for the full length
+                        let step = prime.pow(twiddles.root, (start + j) as u64);
+                        let mut twiddle = twiddles.one;
+                        for frequency in 0..rows {
+                            let index = layout.frequency(frequency);
+                            out[index * 3 + i] = prime.mul(values[index], twiddle);
+                            twiddle = prime.mul(twiddle, step);
+                        }
+                    }
+                });
+            write_panel(&transform.file, layout, start, width, &panel)?;
+        }
+        Ok(transform)
+    }
+
+    /// Carry the first `len` coefficients of a transform file into limbs.
+    fn carry(&self, transform: &TransformFile, len: u64) -> io::Result<LimbFile> {
+        let mut reader = BufReader::new(&transform.file);
+        reader.seek(SeekFrom::Start(0))?;
+        let mut writer = self.create()?;
+        let mut sum = Carry::default();
+        let mut bytes = [0; ELEMENT_BYTES];
+        for _ in 0..len {
+            reader.read_exact(&mut bytes)?;
+            sum.add(std::array::from_fn(|i| {
+                u64::from_le_bytes(bytes[i * 8..i * 8 + 8].try_into().expect("8 bytes"))
+            }));
+            writer.push(sum.take(self.radix))?;
+        }
+        while !sum.is_zero() {
+            writer.push(sum.take(self.radix))?;
+        }
+        writer.finish()
+    }
+
+    /// Write a value in decimal radix to `path` in decimal, a block of limbs at a time from
+    /// the most significant.
+    fn write_decimal(&self, value: &LimbFile, path: &Path) -> io::Result<()> {
+        let mut out = BufWriter::new(File::create(path)?);
+        if value.limbs == 0 {
+            out.write_all(b"0")?;
+        }
+        let source = File::open(&value.path)?;
+        let mut text = String::new();
+        let mut end = value.limbs;
+        while end > 0 {
+            let start = end.saturating_sub(self.block);
+            let mut bytes = vec![0; usize::try_from((end - start) * 8).expect("block fits")];
+            read_at(&source, start * 8, &mut bytes)?;
+            text.clear();
+            for (i, limb) in bytes.rchunks_exact(8).enumerate() {
+                let limb = u64::from_le_bytes(limb.try_into().expect("8 bytes"));
+                if end == value.limbs && i == 0 {
+                    write!(text, "{limb}").expect("writing to a String");
+                } else {
+                    write!(text, "{limb:0DECIMAL_DIGITS$}").expect("writing to a String");
+                }
+            }
+            out.write_all(text.as_bytes())?;
+            end = start;
+        }
+        out.flush()
+    }
+}
+
+/// Middle transform steps: transform the rows of both operands, form the doubling
+/// combinations pointwise, transform back and remove the twist.
+fn double_rows(
+    low: &TransformFile,
+    high: &TransformFile,
+    layout: Layout,
+    twiddles: &[Twiddles; 3],
+) -> io::Result<()> {
+    let (rows, columns) = (layout.rows(), layout.columns());
+    for first in (0..rows).step_by(layout.batch) {
+        let count = layout.batch.min(rows - first);
+        let offset = layout.offset(first, 0);
+        let mut low_rows = read_elements(&low.file, offset, count * columns)?;
+        let mut high_rows = read_elements(&high.file, offset, count * columns)?;
+        low_rows
+            .par_chunks_mut(columns * 3)
+            .zip(high_rows.par_chunks_mut(columns * 3))
+            .enumerate()
+            .for_each(|(r, (low_row, high_row))| {
+                let frequency = layout.frequency(first + r) as u64;
+                for (i, twiddles) in twiddles.iter().enumerate() {
+                    let prime = twiddles.prime;
+                    let mut x: Vec<u64> = low_row.iter().skip(i).step_by(3).copied().collect();
+                    let mut y: Vec<u64> = high_row.iter().skip(i).step_by(3).copied().collect();
+                    prime.forward(&mut x, &twiddles.row_roots);
+                    prime.forward(&mut y, &twiddles.row_roots);
+                    for (u, v) in x.iter_mut().zip(&mut y) {
+                        let (a, b) = (*u, *v);
+                        let odd = prime.add(prime.mul(a, a), prime.mul(b, b));
+                        let even = prime.mul(b, prime.add(b, prime.add(a, a)));
+                        *u = prime.mul(odd, twiddles.scale);
+                        *v = prime.mul(even, twiddles.scale);
+                    }
+                    prime.inverse_transform(&mut x, &twiddles.row_roots);
+                    prime.inverse_transform(&mut y, &twiddles.row_roots);
+
+                    let step = prime.pow(twiddles.inverse_root, frequency);
+                    let mut twiddle = twiddles.one;
+                    for (c, (&u, &v)) in x.iter().zip(&y).enumerate() {
+                        low_row[c * 3 + i] = prime.mul(u, twiddle);
+                        high_row[c * 3 + i] = prime.mul(v, twiddle);
+                        twiddle = prime.mul(twiddle, step);
+                    }
+                }
+            });
+        write_at(&low.file, offset, &to_bytes(&low_rows))?;
+        write_at(&high.file, offset, &to_bytes(&high_rows))?;
+    }
+    Ok(())
+}
+
+/// Last transform step: transform the columns back and replace each element with its
+/// coefficient, recovered by the Chinese remainder theorem.
+fn inverse_columns(
+    transform: &TransformFile,
+    layout: Layout,
+    twiddles: &[Twiddles; 3],
+    garner: &Garner,
+) -> io::Result<()> {
+    let (rows, columns) = (layout.rows(), layout.columns());
+    for start in (0..columns).step_by(layout.panel) {
+        let width = layout.panel.min(columns - start);
+        let mut panel = read_panel(&transform.file, layout, start, width)?;
+        panel.par_chunks_mut(rows * 3).for_each(|column| {
+            let residues: [Vec<u64>; 3] = std::array::from_fn(|i| {
+                let mut values: Vec<u64> = column.iter().skip(i).step_by(3).copied().collect();
+                twiddles[i]
+                    .prime
+                    .inverse_transform(&mut values, &twiddles[i].column_roots);
+                values
+            });
+            for (row, element) in column.chunks_exact_mut(3).enumerate() {
+                element.copy_from_slice(&garner.words(
+                    residues[0][row],
+                    residues[1][row],
+                    residues[2][row],
+                ));
+            }
+        });
+        write_panel(&transform.file, layout, start, width, &panel)?;
+    }
+    Ok(())
+}
+
+/// Read `bytes.len()` bytes at byte `offset` of `file`.
+fn read_at(mut file: &File, offset: u64, bytes: &mut [u8]) -> io::Result<()> {
+    file.seek(SeekFrom::Start(offset))?;
+    file.read_exact(bytes)
+}
+
+/// Write `bytes` at byte `offset` of `file`.
+fn write_at(mut file: &File, offset: u64, bytes: &[u8]) -> io::Result<()> {
+    file.seek(SeekFrom::Start(offset))?;
+    file.write_all(bytes)
+}
+
+/// Read `len` transform elements at byte `offset` of `file`, as residue triples.
+fn read_elements(file: &File, offset: u64, len: usize) -> io::Result<Vec<u64>> {
+    let mut bytes = vec![0; len * ELEMENT_BYTES];
+    read_at(file, offset, &mut bytes)?;
+    Ok(bytes
+        .chunks_exact(8)
+        .map(|word| u64::from_le_bytes(word.try_into().expect("8 bytes")))
+        .collect())
+}
+
+fn to_bytes(words: &[u64]) -> Vec<u8> {
+    words.iter().flat_map(|word| word.to_le_bytes()).collect()
+}
+
+/// Read the columns `start..start + width` of every row, as residue triples in column-major
+/// order.
+fn read_panel(file: &File, layout: Layout, start: usize, width: usize) -> io::Result<Vec<u64>> {
+    let rows = layout.rows();
+    let mut panel = vec![0; rows * width * 3];
+    for row in 0..rows {
+        let elements = read_elements(file, layout.offset(row, start), width)?;
+        for (j, element) in elements.chunks_exact(3).enumerate() {
+            panel[(j * rows + row) * 3..][..3].copy_from_slice(element);
+        }
+    }
+    Ok(panel)
+}
+
+/// Write a panel in the order of [`read_panel`] back to its rows.
+fn write_panel(
+    file: &File,
+    layout: Layout,
+    start: usize,
+    width: usize,
+    panel: &[u64],
+) -> io::Result<()> {
+    let rows = layout.rows();
+    let mut bytes = Vec::with_capacity(width * ELEMENT_BYTES);
+    for row in 0..rows {
+        bytes.clear();
+        for j in 0..width {
+            for word in &panel[(j * rows + row) * 3..][..3] {
+                bytes.extend(word.to_le_bytes());
+            }
+        }
+        write_at(file, layout.offset(row, start), &bytes)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A fresh scratch directory for one test.
+    fn scratch_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("fib-rs-{}-{name}", process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        dir
+    }
+
+    /// The value of a limb file in `radix`.
+    fn read(file: &LimbFile, radix: Radix) -> BigUint {
+        let bytes = fs::read(&file.path).unwrap();
+        assert_eq!(bytes.len() as u64, file.limbs * 8);
+        bytes.rchunks_exact(8).fold(BigUint::ZERO, |value, limb| {
+            value * radix.base() + u64::from_le_bytes(limb.try_into().unwrap())
+        })
+    }
+
+    #[test]
+    fn doubling_steps_on_disk() {
+        let dir = scratch_dir("doubling");
+        fs::create_dir_all(&dir).unwrap();
+        for radix in [Radix::Binary, Radix::Decimal] {
+            // The smallest memory limit splits the transforms into many panels and batches
+            let scratch = Scratch {
+                dir: &dir,
+                block: MIN_BLOCK_LIMBS,
+                memory: 0,
+                radix,
+            };
+            for k in [1, 2, 93, 94, 1_000, 12_345] {
+                let (current, next) = Fib::fib_fast_doubling_helper(k);
+                let current = scratch.store(&current).unwrap();
+                let next = scratch.store(&next).unwrap();
+                let (odd, high) = scratch.double(current, next).unwrap();
+                assert_eq!(
+                    read(&odd, radix),
+                    Fib::single(2 * k + 1),
+                    "F(2k+1), k = {k}"
+                );
+                assert_eq!(
+                    read(&high, radix),
+                    Fib::single(2 * k + 2),
+                    "F(2k+2), k = {k}"
+                );
+                let even = scratch.sub(&high, &odd).unwrap();
+                assert_eq!(read(&even, radix), Fib::single(2 * k), "F(2k), k = {k}");
+            }
+        }
+        assert_eq!(
+            fs::read_dir(&dir).unwrap().count(),
+            0,
+            "scratch files removed"
+        );
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn carries_in_either_radix() {
+        let words = [u64::MAX, u64::MAX, u64::MAX];
+        let value = (BigUint::from(1u32) << 192) - 1u32;
+        for radix in [Radix::Binary, Radix::Decimal] {
+            let mut sum = Carry::default();
+            sum.add(words);
+            sum.add(words);
+            let mut limbs = Vec::new();
+            while !sum.is_zero() {
+                limbs.push(sum.take(radix));
+            }
+            let total = limbs
+                .iter()
+                .rev()
+                .fold(BigUint::ZERO, |total, &limb| total * radix.base() + limb);
+            assert_eq!(total, &value * 2u32);
+            assert_eq!(radix.limbs(&total), limbs);
+        }
+    }
+
+    #[test]
+    fn files_match_single() {
+        let dir = scratch_dir("single");
+        // The smallest blocks put every value above a thousand bits on disk
+        let config = OutOfCore::new(dir.join("scratch")).memory_limit(0);
+        let path = dir.join("value");
+        for n in [0, 1, 90, 1_000, 20_000, 20_001, 33_333] {
+            let expected = Fib::single(n);
+
+            Fib::single_to_file(n, &path, FileFormat::Binary, &config).unwrap();
+            assert_eq!(
+                BigUint::from_bytes_le(&fs::read(&path).unwrap()),
+                expected,
+                "F({n})"
+            );
+
+            Fib::single_to_file(n, &path, FileFormat::Decimal, &config).unwrap();
+            assert_eq!(
+                fs::read_to_string(&path).unwrap(),
+                expected.to_string(),
+                "F({n})"
+            );
+        }
+        assert_eq!(fs::read_dir(config.scratch_dir()).unwrap().count(), 0);
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn sizes_follow_memory_limit() {
+        assert_eq!(OutOfCore::new("x").block_limbs(), (1 << 30) / 8 / 16);
+        assert_eq!(OutOfCore::new("x").memory_limit(0).block_limbs(), 16);
+        assert_eq!(
+            OutOfCore::new("x").memory_limit(1 << 20).block_limbs(),
+            1 << 13
+        );
+
+        // 2^20 elements of 24 bytes in a quarter of the limit: rows of 2^18 elements
+        let layout = Layout::new(30, 96 << 20);
+        assert_eq!((layout.log_rows, layout.log_columns), (12, 18));
+        assert_eq!((layout.panel, layout.batch), (1 << 8, 2));
+        // Short transforms are a single row
+        let layout = Layout::new(10, 96 << 20);
+        assert_eq!(
+            (layout.rows(), layout.columns(), layout.batch),
+            (1, 1 << 10, 1)
+        );
+        assert_eq!(Layout::new(10, 0).columns(), 16);
+    }
+}
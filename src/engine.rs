@@ -0,0 +1,413 @@
+//! A configurable Fibonacci engine.
+//!
+//! The associated functions of [`Fib`] always run on the global rayon pool with balanced range
+//! chunks. A [`FibEngine`] carries those choices as configuration instead, so several
+//! services in one process can each use their own thread pool, chunking policy, algorithm and
+//! memory limit.
+
+use std::{num::NonZeroUsize, sync::Arc};
+
+use num_bigint::BigUint;
+use rayon::{ThreadPool, current_num_threads};
+
+use crate::{
+    Algorithm, Control, Fib, FibError, MemoryGuard, error, estimate::LOG2_PHI_UPPER, verify,
+};
+
+/// How a range is split into chunks that are computed in parallel.
+///
+/// The first chunk is seeded with a fast doubling computation and every later chunk from its
+/// neighbour with the addition formula; each chunk is then extended by additions, so more chunks
+/// add a little seeding work but balance the load better.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum Chunking {
+    /// Several chunks per thread of the pool, sized so each holds about the same number of bits
+    ///
+    /// Later values of a range are larger, so equal-cost chunks get shorter towards its end, and
+    /// having more chunks than threads lets idle threads steal the remaining ones.
+    #[default]
+    Balanced,
+    /// One chunk per thread of the pool, each with the same number of values
+    PerThread,
+    /// Chunks of at most this many values
+    ChunkLen(NonZeroUsize),
+    /// This many chunks of equal length, or fewer for short ranges
+    ChunkCount(NonZeroUsize),
+}
+
+impl Chunking {
+    /// Offsets at which the chunks of a range of `total_count` values from index `start` begin,
+    /// followed by `total_count` itself.
+    pub(crate) fn bounds(self, start: u128, total_count: usize) -> Vec<usize> {
+        let chunk_len = match self {
+            Self::Balanced => {
+                let chunks = current_num_threads().saturating_mul(BALANCED_CHUNKS_PER_THREAD);
+                return balanced_bounds(start, total_count, chunks);
+            }
+            Self::PerThread => (total_count / current_num_threads()).max(1),
+            Self::ChunkLen(len) => len.get(),
+            Self::ChunkCount(count) => total_count.div_ceil(count.get()).max(1),
+        };
+        (0..total_count)
+            .step_by(chunk_len)
+            .chain([total_count])
+            .collect()
+    }
+}
+
+/// Number of chunks per thread made by [`Chunking::Balanced`].
+const BALANCED_CHUNKS_PER_THREAD: usize = 4;
+
+/// Cost of allocating and storing one value, in bits of addition work.
+const VALUE_COST_BITS: f64 = 256.0;
+
+/// Chunk offsets splitting `total_count` values from index `start` into up to `chunks` chunks
+/// of about equal cost, where F(i) costs its bit length plus a fixed cost per value.
+#[allow(
+    clippy::cast_precision_loss,
+    clippy::cast_possible_truncation,
+    clippy::cast_sign_loss
+)] // Boundaries only need to be approximately balanced
+pub(crate) fn balanced_bounds(start: u128, total_count: usize, chunks: usize) -> Vec<usize> {
+    let chunks = chunks.clamp(1, total_count.max(1));
+    // The value at offset t costs base + slope * t, so the first t values cost
+    // base * t + slope * t^2 / 2
+    let slope = LOG2_PHI_UPPER;
+    let base = start as f64 * LOG2_PHI_UPPER + VALUE_COST_BITS;
+    let count = total_count as f64;
+    let total = base * count + slope * count * count / 2.0;
+
+    let mut bounds = vec![0];
+    for chunk in 1..chunks {
+        let target = total * chunk as f64 / chunks as f64;
+        // Positive root of slope/2 * t^2 + base * t = target, in a form that stays accurate
+        // when the slope is negligible next to the base
+        let offset = 2.0 * target / (base + (base * base + 2.0 * slope * target).sqrt());
+        let offset = (offset.round() as usize).min(total_count);
+        if bounds.last().is_some_and(|&last| offset > last) {
+            bounds.push(offset);
+        }
+    }
+    if bounds.last().is_some_and(|&last| total_count > last) {
+        bounds.push(total_count);
+    }
+    bounds
+}
+
+/// A Fibonacci calculator with its own thread pool, chunking policy, algorithm and memory limit.
+///
+/// Built with [`FibEngine::builder`]; the default engine behaves like the associated functions
+/// of [`Fib`].
+///
+/// # Examples
+///
+/// ```
+/// use std::{num::NonZeroUsize, sync::Arc};
+/// use fib_rs::{Chunking, Fib, FibEngine, FibError};
+///
+/// let pool = rayon::ThreadPoolBuilder::new().num_threads(2).build().unwrap();
+/// let engine = FibEngine::builder()
+///     .thread_pool(Arc::new(pool))
+///     .chunking(Chunking::ChunkLen(NonZeroUsize::new(1_000).unwrap()))
+///     .memory_limit(1 << 30)
+///     .build();
+///
+/// assert_eq!(engine.single(100).unwrap(), Fib::single(100));
+/// assert_eq!(engine.range(0, 5_000).unwrap(), Fib::range(0, 5_000));
+/// assert!(matches!(engine.range(0, 10_000_000), Err(FibError::MemoryLimit(_))));
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct FibEngine {
+    pool: Option<Arc<ThreadPool>>,
+    chunking: Chunking,
+    algorithm: Algorithm,
+    memory_limit: Option<u64>,
+    self_check: bool,
+}
+
+impl FibEngine {
+    /// Start configuring an engine.
+    #[must_use]
+    pub fn builder() -> FibEngineBuilder {
+        FibEngineBuilder::default()
+    }
+
+    /// The chunking policy used for ranges.
+    #[must_use]
+    pub fn chunking(&self) -> Chunking {
+        self.chunking
+    }
+
+    /// The algorithm used for single values.
+    #[must_use]
+    pub fn algorithm(&self) -> Algorithm {
+        self.algorithm
+    }
+
+    /// The memory limit in bytes, if any.
+    #[must_use]
+    pub fn memory_limit(&self) -> Option<u64> {
+        self.memory_limit
+    }
+
+    /// Whether single values are checked with [`Fib::verify`] before being returned.
+    #[must_use]
+    pub fn self_check(&self) -> bool {
+        self.self_check
+    }
+
+    /// Calculate the nth Fibonacci number.
+    ///
+    /// # Arguments
+    ///
+    /// * `n` - The index of the Fibonacci number to calculate
+    ///
+    /// # Returns
+    ///
+    /// * The nth Fibonacci number as a `BigUint`
+    ///
+    /// # Errors
+    ///
+    /// * `FibError::MemoryLimit` if the estimated peak memory exceeds the engine's limit
+    /// * `FibError::VerificationFailed` if self-checking is enabled and the value fails it
+    pub fn single(&self, n: u128) -> Result<BigUint, FibError> {
+        self.single_with(n, &Control::new())
+    }
+
+    /// Calculate the nth Fibonacci number, observing a cancellation token, a deadline and a
+    /// progress callback.
+    ///
+    /// # Arguments
+    ///
+    /// * `n` - The index of the Fibonacci number to calculate
+    /// * `control` - The cancellation token, deadline and progress callback to observe
+    ///
+    /// # Returns
+    ///
+    /// * The nth Fibonacci number as a `BigUint`
+    ///
+    /// # Errors
+    ///
+    /// * `FibError::MemoryLimit` if the estimated peak memory exceeds the engine's limit
+    /// * `FibError::Cancelled` or `FibError::DeadlineExceeded` as for [`Fib::single_with`]
+    /// * `FibError::VerificationFailed` if self-checking is enabled and the value fails it
+    pub fn single_with(&self, n: u128, control: &Control) -> Result<BigUint, FibError> {
+        self.install(|| {
+            if let Some(limit) = self.memory_limit {
+                MemoryGuard::new(limit).check_single(n)?;
+            }
+            let value = self.algorithm.single_with(n, control)?;
+            if self.self_check {
+                verify::check(n, value)
+            } else {
+                Ok(value)
+            }
+        })
+    }
+
+    /// Generate Fibonacci numbers for indices in the given inclusive range.
+    ///
+    /// # Arguments
+    ///
+    /// * `start` - The starting index of the range
+    /// * `end` - The ending index of the range (inclusive)
+    ///
+    /// # Returns
+    ///
+    /// * A `Vec<BigUint>` containing ordered Fibonacci numbers for indices in the specified inclusive range.
+    ///
+    /// # Errors
+    ///
+    /// * `FibError::InvertedRange` or `FibError::CountOverflow` as for [`Fib::try_range`]
+    /// * `FibError::MemoryLimit` if the estimated peak memory exceeds the engine's limit
+    pub fn range(&self, start: u128, end: u128) -> Result<Vec<BigUint>, FibError> {
+        self.range_with(start, end, &Control::new())
+    }
+
+    /// Generate Fibonacci numbers for indices in the given inclusive range, observing a
+    /// cancellation token, a deadline and a progress callback.
+    ///
+    /// # Arguments
+    ///
+    /// * `start` - The starting index of the range
+    /// * `end` - The ending index of the range (inclusive)
+    /// * `control` - The cancellation token, deadline and progress callback to observe
+    ///
+    /// # Returns
+    ///
+    /// * A `Vec<BigUint>` containing ordered Fibonacci numbers for indices in the specified inclusive range.
+    ///
+    /// # Errors
+    ///
+    /// * `FibError::InvertedRange` or `FibError::CountOverflow` as for [`Fib::try_range`]
+    /// * `FibError::MemoryLimit` if the estimated peak memory exceeds the engine's limit
+    /// * `FibError::Cancelled` or `FibError::DeadlineExceeded` as for [`Fib::range_with`]
+    pub fn range_with(
+        &self,
+        start: u128,
+        end: u128,
+        control: &Control,
+    ) -> Result<Vec<BigUint>, FibError> {
+        error::range_len(start, end)?;
+        self.install(|| {
+            if let Some(limit) = self.memory_limit {
+                MemoryGuard::new(limit).check_range(start, end)?;
+            }
+            Fib::range_chunked(start, end, self.chunking, control)
+        })
+    }
+
+    /// Run `op` on the engine's pool, or on the current pool if none was configured.
+    fn install<T: Send>(&self, op: impl FnOnce() -> T + Send) -> T {
+        match &self.pool {
+            Some(pool) => pool.install(op),
+            None => op(),
+        }
+    }
+}
+
+/// Builder for [`FibEngine`].
+#[derive(Debug, Clone, Default)]
+pub struct FibEngineBuilder {
+    engine: FibEngine,
+}
+
+impl FibEngineBuilder {
+    /// Run computations on `pool` instead of the global rayon pool.
+    #[must_use]
+    pub fn thread_pool(mut self, pool: Arc<ThreadPool>) -> Self {
+        self.engine.pool = Some(pool);
+        self
+    }
+
+    /// Split ranges according to `chunking`.
+    #[must_use]
+    pub fn chunking(mut self, chunking: Chunking) -> Self {
+        self.engine.chunking = chunking;
+        self
+    }
+
+    /// Compute single values with `algorithm`.
+    #[must_use]
+    pub fn algorithm(mut self, algorithm: Algorithm) -> Self {
+        self.engine.algorithm = algorithm;
+        self
+    }
+
+    /// Refuse computations whose estimated peak memory exceeds `limit_bytes`.
+    #[must_use]
+    pub fn memory_limit(mut self, limit_bytes: u64) -> Self {
+        self.engine.memory_limit = Some(limit_bytes);
+        self
+    }
+
+    /// Check every single value with [`Fib::verify`] before returning it.
+    #[must_use]
+    pub fn self_check(mut self, enabled: bool) -> Self {
+        self.engine.self_check = enabled;
+        self
+    }
+
+    /// Finish configuring the engine.
+    #[must_use]
+    pub fn build(self) -> FibEngine {
+        self.engine
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rayon::ThreadPoolBuilder;
+
+    #[test]
+    fn chunk_bounds() {
+        let ten = NonZeroUsize::new(10).unwrap();
+        assert_eq!(Chunking::ChunkLen(ten).bounds(0, 5), [0, 5]);
+        assert_eq!(Chunking::ChunkLen(ten).bounds(0, 25), [0, 10, 20, 25]);
+        assert_eq!(
+            Chunking::ChunkCount(ten).bounds(0, 95),
+            [0, 10, 20, 30, 40, 50, 60, 70, 80, 90, 95]
+        );
+        assert_eq!(Chunking::ChunkCount(ten).bounds(0, 5), [0, 1, 2, 3, 4, 5]);
+        assert_eq!(Chunking::PerThread.bounds(0, 1), [0, 1]);
+        assert_eq!(Chunking::Balanced.bounds(0, 1), [0, 1]);
+    }
+
+    #[test]
+    fn balanced_chunks_hold_equal_bits() {
+        let bounds = balanced_bounds(0, 20_000, 8);
+        assert_eq!(bounds.len(), 9);
+        assert_eq!((bounds[0], bounds[8]), (0, 20_000));
+        // Chunks shrink towards the end of the range as their values grow
+        assert!(bounds.windows(3).all(|w| w[1] - w[0] > w[2] - w[1]));
+        #[allow(clippy::cast_precision_loss)] // Costs only need to be approximately equal
+        let costs: Vec<f64> = bounds
+            .windows(2)
+            .map(|w| {
+                (w[0]..w[1])
+                    .map(|i| Fib::bit_len(i as u128) as f64 + VALUE_COST_BITS)
+                    .sum()
+            })
+            .collect();
+        let min = costs.iter().copied().fold(f64::INFINITY, f64::min);
+        let max = costs.iter().copied().fold(0.0, f64::max);
+        assert!(max - min < max / 100.0, "{costs:?}");
+
+        // Far from zero the values are almost the same size, so chunks are almost equal
+        let bounds = balanced_bounds(u128::from(u64::MAX), 1_000, 4);
+        assert_eq!(bounds, [0, 250, 500, 750, 1_000]);
+        assert_eq!(balanced_bounds(0, 3, 8), [0, 1, 2, 3]);
+    }
+
+    #[test]
+    fn engines_match_static_functions() {
+        let pool = Arc::new(ThreadPoolBuilder::new().num_threads(3).build().unwrap());
+        let policies = [
+            Chunking::Balanced,
+            Chunking::PerThread,
+            Chunking::ChunkLen(NonZeroUsize::new(1).unwrap()),
+            Chunking::ChunkLen(NonZeroUsize::new(37).unwrap()),
+            Chunking::ChunkCount(NonZeroUsize::new(1).unwrap()),
+            Chunking::ChunkCount(NonZeroUsize::new(64).unwrap()),
+        ];
+        for chunking in policies {
+            let engine = FibEngine::builder()
+                .thread_pool(Arc::clone(&pool))
+                .chunking(chunking)
+                .build();
+            for (start, end) in [(0, 0), (0, 100), (17, 1_000), (5_000, 5_003)] {
+                assert_eq!(engine.range(start, end).unwrap(), Fib::range(start, end));
+            }
+            assert_eq!(engine.single(1_000).unwrap(), Fib::single(1_000));
+            assert_eq!(
+                engine.range(3, 2),
+                Err(FibError::InvertedRange { start: 3, end: 2 })
+            );
+        }
+    }
+
+    #[test]
+    fn memory_limits() {
+        let engine = FibEngine::builder().memory_limit(1 << 20).build();
+        assert_eq!(engine.memory_limit(), Some(1 << 20));
+        assert!(engine.single(1_000).is_ok());
+        assert!(matches!(
+            engine.single(100_000_000),
+            Err(FibError::MemoryLimit(_))
+        ));
+        assert!(FibEngine::default().range(0, 1_000).is_ok());
+    }
+
+    #[test]
+    fn self_checking() {
+        for algorithm in Algorithm::ALL {
+            let engine = FibEngine::builder()
+                .algorithm(algorithm)
+                .self_check(true)
+                .build();
+            assert!(engine.self_check());
+            assert_eq!(engine.single(2_000).unwrap(), Fib::single(2_000));
+        }
+    }
+}
@@ -0,0 +1,143 @@
+//! The error type of the fallible Fibonacci APIs.
+
+use std::{error::Error, fmt, io};
+
+use crate::LimitExceeded;
+
+/// Errors returned by the fallible Fibonacci computations.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum FibError {
+    /// The end of a range lies before its start
+    InvertedRange {
+        /// The requested starting index
+        start: u128,
+        /// The requested ending index
+        end: u128,
+    },
+    /// The number of values in a range does not fit in `usize` on this target
+    CountOverflow {
+        /// The requested starting index
+        start: u128,
+        /// The requested ending index
+        end: u128,
+    },
+    /// The computation was refused because it would exceed a memory limit
+    MemoryLimit(LimitExceeded),
+    /// The computation was cancelled through its `CancellationToken` before it completed
+    Cancelled,
+    /// The deadline of the computation passed before it completed
+    DeadlineExceeded,
+    /// A computed value failed its independent correctness check
+    VerificationFailed {
+        /// The index of the Fibonacci number that failed
+        n: u128,
+    },
+    /// Reading or writing a file or stream failed, whether the output or a scratch file
+    Io {
+        /// The kind of the underlying I/O error
+        kind: io::ErrorKind,
+        /// The message of the underlying I/O error
+        message: String,
+    },
+}
+
+impl fmt::Display for FibError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvertedRange { start, end } => {
+                write!(f, "range end {end} is before its start {start}")
+            }
+            Self::CountOverflow { start, end } => {
+                write!(
+                    f,
+                    "range {start}..={end} has too many values for this target"
+                )
+            }
+            Self::MemoryLimit(exceeded) => exceeded.fmt(f),
+            Self::Cancelled => f.write_str("computation was cancelled"),
+            Self::DeadlineExceeded => f.write_str("computation exceeded its deadline"),
+            Self::VerificationFailed { n } => {
+                write!(f, "computed value of F({n}) failed verification")
+            }
+            Self::Io { message, .. } => write!(f, "I/O error: {message}"),
+        }
+    }
+}
+
+impl Error for FibError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            Self::MemoryLimit(exceeded) => Some(exceeded),
+            _ => None,
+        }
+    }
+}
+
+impl From<LimitExceeded> for FibError {
+    fn from(exceeded: LimitExceeded) -> Self {
+        Self::MemoryLimit(exceeded)
+    }
+}
+
+impl From<io::Error> for FibError {
+    fn from(error: io::Error) -> Self {
+        Self::Io {
+            kind: error.kind(),
+            message: error.to_string(),
+        }
+    }
+}
+
+/// Number of values in the inclusive range `start..=end`.
+pub(crate) fn range_len(start: u128, end: u128) -> Result<usize, FibError> {
+    if end < start {
+        return Err(FibError::InvertedRange { start, end });
+    }
+    (end - start)
+        .checked_add(1)
+        .and_then(|count| usize::try_from(count).ok())
+        .ok_or(FibError::CountOverflow { start, end })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn range_lengths() {
+        assert_eq!(range_len(0, 0), Ok(1));
+        assert_eq!(range_len(3, 10), Ok(8));
+        assert_eq!(
+            range_len(10, 5),
+            Err(FibError::InvertedRange { start: 10, end: 5 })
+        );
+        assert_eq!(
+            range_len(0, u128::MAX),
+            Err(FibError::CountOverflow {
+                start: 0,
+                end: u128::MAX
+            })
+        );
+        assert_eq!(
+            range_len(0, u128::from(u64::MAX) << 1),
+            Err(FibError::CountOverflow {
+                start: 0,
+                end: u128::from(u64::MAX) << 1
+            })
+        );
+    }
+
+    #[test]
+    fn messages() {
+        assert_eq!(
+            FibError::InvertedRange { start: 10, end: 5 }.to_string(),
+            "range end 5 is before its start 10"
+        );
+        assert_eq!(FibError::Cancelled.to_string(), "computation was cancelled");
+        assert_eq!(
+            FibError::from(io::Error::new(io::ErrorKind::NotFound, "no such file")).to_string(),
+            "I/O error: no such file"
+        );
+    }
+}
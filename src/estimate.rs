@@ -0,0 +1,296 @@
+//! Resource estimation and memory limits.
+//!
+//! The size of F(n) is known in advance from its bit length (see [`Fib::bit_len`]), so the
+//! memory needed by a request can be predicted before any work starts:
+//!
+//! - A single F(n) needs its output plus a bounded number of temporaries of the same order
+//!   during the final doubling steps.
+//! - A range holds every output value at once, and since bits(F(i)) grows linearly with i,
+//!   the total grows quadratically with the end of the range.
+//!
+//! [`MemoryGuard`] uses these estimates to refuse requests that would exceed a limit.
+
+use std::{error::Error, fmt, mem::size_of};
+
+use num_bigint::BigUint;
+use rayon::current_num_threads;
+
+use crate::{Fib, FibError, error, mul};
+
+/// Peak memory of a single computation, as a multiple of the size of its result, indexed by
+/// the number of levels of parallel splitting of large multiplications.
+///
+/// Sequentially, the final multiplication holds its two operands, each about half the size of
+/// the result, the result itself, and the scratch space of Toom-3 multiplication, which
+/// together peak just below six results. Each level of splitting keeps split operands and
+/// partial products alive and runs three times as many Toom-3 multiplications at once. The
+/// factors leave some headroom above measured peaks.
+const SINGLE_PEAK_FACTORS: [u64; 4] = [7, 11, 17, 26];
+
+/// Upper bound on log2(φ), the growth in bits of F(n) per index.
+pub(crate) const LOG2_PHI_UPPER: f64 = 0.694_242;
+
+/// Predicted resource usage of a Fibonacci computation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Estimate {
+    /// Bytes occupied by the returned value(s), including per-value bookkeeping
+    pub output_bytes: u64,
+    /// Predicted peak memory in bytes while computing, including the output
+    pub peak_bytes: u64,
+}
+
+impl Fib {
+    /// Estimate the memory needed to compute the nth Fibonacci number.
+    ///
+    /// The output size is exact, derived from [`Fib::bit_len`]; the peak accounts for the
+    /// temporaries of the final doubling steps, which grow when large multiplications are split
+    /// across the threads of the current pool.
+    ///
+    /// # Arguments
+    ///
+    /// * `n` - The index of the Fibonacci number
+    ///
+    /// # Returns
+    ///
+    /// * An `Estimate` of the output size and peak memory in bytes
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use fib_rs::Fib;
+    ///
+    /// let estimate = Fib::estimate_single(10_000_000);
+    /// assert!(estimate.output_bytes > 800_000); // about 6.9 million bits
+    /// assert!(estimate.peak_bytes > estimate.output_bytes);
+    /// ```
+    #[must_use]
+    pub fn estimate_single(n: u128) -> Estimate {
+        let output_bytes = value_bytes(Self::bit_len(n));
+        Estimate {
+            output_bytes,
+            peak_bytes: output_bytes.saturating_mul(single_peak_factor()),
+        }
+    }
+
+    /// Estimate the memory needed to compute the Fibonacci numbers F(start) through F(end).
+    ///
+    /// The output size sums an upper bound on the size of every value in the range; the peak
+    /// adds the working memory of each parallel chunk seeding itself near the end of the range.
+    ///
+    /// # Arguments
+    ///
+    /// * `start` - The starting index of the range
+    /// * `end` - The ending index of the range (inclusive)
+    ///
+    /// # Returns
+    ///
+    /// * An `Estimate` of the output size and peak memory in bytes (zero if `end < start`)
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use fib_rs::Fib;
+    ///
+    /// // Ten million values averaging millions of bits each: far too large to hold
+    /// let estimate = Fib::estimate_range(0, 10_000_000);
+    /// assert!(estimate.output_bytes > 1 << 40);
+    /// ```
+    #[must_use]
+    #[allow(clippy::cast_precision_loss)] // Estimates only need a few significant digits
+    pub fn estimate_range(start: u128, end: u128) -> Estimate {
+        if end < start {
+            return Estimate::default();
+        }
+
+        // Σ bits(F(i)) ≤ Σ (i * log2(φ) + 1) over the range, plus per-value limb rounding
+        let count = (end - start) as f64 + 1.0;
+        let index_sum = (start as f64 + end as f64) * count / 2.0;
+        let bits = index_sum * LOG2_PHI_UPPER + count;
+        let bytes = bits / 8.0 + count * (size_of::<u64>() + size_of::<BigUint>()) as f64;
+        let output_bytes = saturating_bytes(bytes);
+
+        let per_thread = Self::estimate_single(end).peak_bytes;
+        let threads = u64::try_from(current_num_threads()).unwrap_or(u64::MAX);
+        Estimate {
+            output_bytes,
+            peak_bytes: output_bytes.saturating_add(per_thread.saturating_mul(threads)),
+        }
+    }
+}
+
+/// Peak memory factor of a single computation on the current pool.
+fn single_peak_factor() -> u64 {
+    let depth = usize::try_from(mul::split_depth()).unwrap_or(usize::MAX);
+    SINGLE_PEAK_FACTORS[depth.min(SINGLE_PEAK_FACTORS.len() - 1)]
+}
+
+/// Refuses computations whose estimated peak memory exceeds a configured limit.
+///
+/// # Examples
+///
+/// ```
+/// use fib_rs::MemoryGuard;
+///
+/// let guard = MemoryGuard::new(1 << 30); // 1 GiB
+/// assert!(guard.range(0, 1_000).is_ok());
+/// assert!(guard.range(0, 10_000_000).is_err()); // refused up front instead of running out of memory
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MemoryGuard {
+    limit_bytes: u64,
+}
+
+impl MemoryGuard {
+    /// Create a guard allowing computations with a predicted peak of at most `limit_bytes`.
+    #[must_use]
+    pub fn new(limit_bytes: u64) -> Self {
+        Self { limit_bytes }
+    }
+
+    /// The configured limit in bytes.
+    #[must_use]
+    pub fn limit_bytes(self) -> u64 {
+        self.limit_bytes
+    }
+
+    /// Check whether computing F(n) fits within the limit.
+    ///
+    /// # Errors
+    ///
+    /// Returns `LimitExceeded` if the estimated peak memory exceeds the limit.
+    pub fn check_single(self, n: u128) -> Result<Estimate, LimitExceeded> {
+        self.check(Fib::estimate_single(n))
+    }
+
+    /// Check whether computing F(start) through F(end) fits within the limit.
+    ///
+    /// # Errors
+    ///
+    /// Returns `LimitExceeded` if the estimated peak memory exceeds the limit.
+    pub fn check_range(self, start: u128, end: u128) -> Result<Estimate, LimitExceeded> {
+        self.check(Fib::estimate_range(start, end))
+    }
+
+    /// Compute F(n) with [`Fib::single`] if it fits within the limit.
+    ///
+    /// # Errors
+    ///
+    /// Returns `FibError::MemoryLimit` without computing anything if the estimated peak memory
+    /// exceeds the limit.
+    pub fn single(self, n: u128) -> Result<BigUint, FibError> {
+        self.check_single(n)?;
+        Ok(Fib::single(n))
+    }
+
+    /// Compute F(start) through F(end) with [`Fib::try_range`] if it fits within the limit.
+    ///
+    /// # Errors
+    ///
+    /// * `FibError::InvertedRange` or `FibError::CountOverflow` for an invalid range
+    /// * `FibError::MemoryLimit`, without computing anything, if the estimated peak memory
+    ///   exceeds the limit
+    pub fn range(self, start: u128, end: u128) -> Result<Vec<BigUint>, FibError> {
+        error::range_len(start, end)?;
+        self.check_range(start, end)?;
+        Fib::try_range(start, end)
+    }
+
+    fn check(self, estimate: Estimate) -> Result<Estimate, LimitExceeded> {
+        if estimate.peak_bytes > self.limit_bytes {
+            Err(LimitExceeded {
+                estimate,
+                limit_bytes: self.limit_bytes,
+            })
+        } else {
+            Ok(estimate)
+        }
+    }
+}
+
+/// Error returned when a computation is predicted to exceed a memory limit.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LimitExceeded {
+    /// The estimate that was refused
+    pub estimate: Estimate,
+    /// The limit it was checked against, in bytes
+    pub limit_bytes: u64,
+}
+
+impl fmt::Display for LimitExceeded {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "estimated peak memory of {} bytes exceeds the limit of {} bytes",
+            self.estimate.peak_bytes, self.limit_bytes
+        )
+    }
+}
+
+impl Error for LimitExceeded {}
+
+/// Bytes held by a `BigUint` of the given bit length: its limbs plus the struct itself.
+fn value_bytes(bits: u128) -> u64 {
+    let limbs = bits.div_ceil(u128::from(u64::BITS));
+    let bytes = limbs
+        .saturating_mul(size_of::<u64>() as u128)
+        .saturating_add(size_of::<BigUint>() as u128);
+    u64::try_from(bytes).unwrap_or(u64::MAX)
+}
+
+/// Convert a floating-point byte count to `u64`, saturating at the bounds.
+#[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)] // Float casts saturate
+fn saturating_bytes(bytes: f64) -> u64 {
+    bytes.ceil() as u64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn actual_bytes(values: &[BigUint]) -> u64 {
+        values
+            .iter()
+            .map(|v| value_bytes(u128::from(v.bits())))
+            .sum()
+    }
+
+    #[test]
+    fn single_estimates_are_exact() {
+        for n in [0, 1, 100, 10_000, 123_456] {
+            let value = Fib::single(n);
+            assert_eq!(
+                Fib::estimate_single(n).output_bytes,
+                value_bytes(u128::from(value.bits()))
+            );
+        }
+    }
+
+    #[test]
+    fn range_estimates_bound_actual_size() {
+        for (start, end) in [(0, 0), (0, 1_000), (500, 5_000), (10_000, 12_000)] {
+            let actual = actual_bytes(&Fib::range(start, end));
+            let estimate = Fib::estimate_range(start, end).output_bytes;
+            assert!(estimate >= actual, "{start}..={end}: {estimate} < {actual}");
+            assert!(estimate <= actual + actual / 4 + 64, "{start}..={end}");
+        }
+        assert_eq!(Fib::estimate_range(10, 5), Estimate::default());
+    }
+
+    #[test]
+    fn guard_refuses_oversized_requests() {
+        let guard = MemoryGuard::new(1 << 20);
+        assert_eq!(guard.single(10).unwrap(), BigUint::from(55u32));
+        let Err(FibError::MemoryLimit(exceeded)) = guard.range(0, 10_000_000) else {
+            panic!("range should exceed the limit");
+        };
+        assert_eq!(exceeded.limit_bytes, 1 << 20);
+        assert!(exceeded.estimate.peak_bytes > 1 << 20);
+        assert_eq!(
+            guard.range(5, 0),
+            Err(FibError::InvertedRange { start: 5, end: 0 })
+        );
+        assert!(guard.check_single(100_000_000).is_err());
+        // Enormous ranges saturate instead of overflowing
+        assert!(guard.check_range(0, u128::MAX).is_err());
+    }
+}
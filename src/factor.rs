@@ -0,0 +1,767 @@
+//! Factorization of Fibonacci numbers.
+//!
+//! F(n) is first split along its algebraic structure: for every divisor d of n the
+//! primitive part
+//!
+//! - P(d) = ∏ F(e)^μ(d/e) over the divisors e of d
+//!
+//! is an exact integer, and F(n) is the product of P(d) over all d dividing n. For
+//! d = 10k with k odd, P(d) divides L(5k), which has the Aurifeuillian factorization
+//!
+//! - L(5k) = L(k) * (L(2k) - 5F(k) + 3) * (L(2k) + 5F(k) + 3)
+//!
+//! giving a further split for free. The remaining cofactors are attacked with trial
+//! division, Pollard's rho and the elliptic curve method (ECM), each limited by a
+//! [`FactorBudget`].
+
+use std::{collections::BTreeMap, fmt};
+
+use num_bigint::{BigInt, BigUint};
+use num_integer::Integer;
+use num_traits::{One, ToPrimitive, Zero};
+use rayon::prelude::*;
+
+use crate::{Fib, is_probable_prime};
+
+/// Trial division bound used when factoring the index n itself.
+const TRIAL_DIVISION_INDEX_BOUND: u128 = 1 << 20;
+
+/// Number of baby steps in ECM stage 2, with giant steps of size 2 * `ECM_BABY_STEPS`.
+const ECM_BABY_STEPS: usize = 105;
+
+/// Effort limits for factoring the cofactors left after the algebraic split.
+///
+/// The defaults are tuned for building factor tables for indices up to a few thousand
+/// in reasonable time; raise them to dig deeper into stubborn composites.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FactorBudget {
+    /// Largest prime tried by trial division
+    pub trial_division_bound: u32,
+    /// Maximum number of Pollard rho iterations per cofactor
+    pub rho_iterations: u64,
+    /// Number of ECM curves tried per cofactor
+    pub ecm_curves: u32,
+    /// ECM stage 1 bound (stage 2 continues up to 100 * B1), at most
+    /// [`FactorBudget::MAX_ECM_B1`]; larger values are treated as that maximum
+    pub ecm_b1: u64,
+}
+
+impl FactorBudget {
+    /// Largest ECM stage 1 bound used, which keeps the sieve of stage 1 primes near 100 MB.
+    pub const MAX_ECM_B1: u64 = 100_000_000;
+}
+
+impl Default for FactorBudget {
+    fn default() -> Self {
+        Self {
+            trial_division_bound: 10_000,
+            rho_iterations: 100_000,
+            ecm_curves: 20,
+            ecm_b1: 10_000,
+        }
+    }
+}
+
+/// How much is known about a factor.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum FactorKind {
+    /// The factor is proven prime (found by trial division or below 2^64)
+    Prime,
+    /// The factor passed the Baillie–PSW test but is not proven prime
+    ProbablePrime,
+    /// The factor is composite and could not be split within the budget
+    Composite,
+}
+
+/// A factor of F(n) together with its multiplicity.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Factor {
+    /// The value of the factor
+    pub value: BigUint,
+    /// The power to which the factor divides F(n)
+    pub exponent: u32,
+    /// Whether the factor is prime, probably prime or an unfactored composite
+    pub kind: FactorKind,
+}
+
+impl fmt::Display for Factor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.value)?;
+        if self.exponent > 1 {
+            write!(f, "^{}", self.exponent)?;
+        }
+        match self.kind {
+            FactorKind::Prime => Ok(()),
+            FactorKind::ProbablePrime => write!(f, " (prp)"),
+            FactorKind::Composite => write!(f, " (composite)"),
+        }
+    }
+}
+
+/// The (possibly partial) factorization of F(n).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Factorization {
+    index: u128,
+    factors: Vec<Factor>,
+}
+
+impl Factorization {
+    /// The index n of the factored Fibonacci number F(n).
+    #[must_use]
+    pub fn index(&self) -> u128 {
+        self.index
+    }
+
+    /// The factors found, sorted by kind and then by value.
+    ///
+    /// Empty for F(0) = 0, F(1) = 1 and F(2) = 1.
+    #[must_use]
+    pub fn factors(&self) -> &[Factor] {
+        &self.factors
+    }
+
+    /// Whether every factor is at least a probable prime.
+    #[must_use]
+    pub fn is_complete(&self) -> bool {
+        self.factors
+            .iter()
+            .all(|factor| factor.kind != FactorKind::Composite)
+    }
+
+    /// Whether every factor is proven prime.
+    #[must_use]
+    pub fn is_proven(&self) -> bool {
+        self.factors
+            .iter()
+            .all(|factor| factor.kind == FactorKind::Prime)
+    }
+}
+
+impl fmt::Display for Factorization {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "F({}) = ", self.index)?;
+        if self.factors.is_empty() {
+            return write!(f, "{}", Fib::single(self.index));
+        }
+        for (i, factor) in self.factors.iter().enumerate() {
+            if i > 0 {
+                write!(f, " * ")?;
+            }
+            write!(f, "{factor}")?;
+        }
+        Ok(())
+    }
+}
+
+impl Fib {
+    /// Factor the nth Fibonacci number using the default [`FactorBudget`].
+    ///
+    /// See [`Fib::factor_with_budget`] for details.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use fib_rs::{Fib, FactorKind};
+    ///
+    /// // F(12) = 144 = 2^4 * 3^2
+    /// let factorization = Fib::factor(12);
+    /// assert_eq!(factorization.to_string(), "F(12) = 2^4 * 3^2");
+    /// assert!(factorization.is_proven());
+    /// ```
+    #[must_use]
+    pub fn factor(n: u128) -> Factorization {
+        Self::factor_with_budget(n, &FactorBudget::default())
+    }
+
+    /// Factor the nth Fibonacci number within the given effort budget.
+    ///
+    /// F(n) is split into its primitive parts P(d) for each divisor d of n, with an extra
+    /// Aurifeuillian split for d = 10k (k odd). Each part is then factored independently and in
+    /// parallel using trial division, Pollard's rho and ECM. Factors that survive all methods
+    /// are reported as [`FactorKind::Composite`].
+    ///
+    /// # Arguments
+    ///
+    /// * `n` - The index of the Fibonacci number to factor
+    /// * `budget` - The effort limits for the generic factoring methods
+    ///
+    /// # Returns
+    ///
+    /// * A `Factorization` whose factors multiply to F(n)
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use fib_rs::{Fib, FactorBudget, FactorKind};
+    /// use num_bigint::BigUint;
+    ///
+    /// let factorization = Fib::factor_with_budget(100, &FactorBudget::default());
+    /// let product = factorization
+    ///     .factors()
+    ///     .iter()
+    ///     .map(|f| f.value.pow(f.exponent))
+    ///     .product::<BigUint>();
+    /// assert_eq!(product, Fib::single(100));
+    /// ```
+    #[must_use]
+    pub fn factor_with_budget(n: u128, budget: &FactorBudget) -> Factorization {
+        let primes = primes_up_to(budget.trial_division_bound);
+
+        // Split F(n) into primitive parts, then factor each part independently
+        let pieces = algebraic_pieces(n);
+        let found: Vec<(BigUint, FactorKind)> = pieces
+            .into_par_iter()
+            .flat_map_iter(|piece| factor_cofactor(piece, &primes, budget))
+            .collect();
+
+        Factorization {
+            index: n,
+            factors: merge_factors(found),
+        }
+    }
+}
+
+/// Split F(n) into its primitive parts, applying the Aurifeuillian split where possible.
+fn algebraic_pieces(n: u128) -> Vec<BigUint> {
+    if n < 3 {
+        return Vec::new();
+    }
+
+    // Enumerate the divisors of n together with their exponent vectors
+    let divisors = divisors_with_exponents(&factor_index(n));
+
+    // Compute F(d) for every divisor d of n once
+    let fib_values: Vec<BigUint> = divisors.par_iter().map(|(d, _)| Fib::single(*d)).collect();
+
+    let mut pieces = Vec::new();
+    for (d, exps) in divisors.iter().filter(|(d, _)| *d >= 3) {
+        // P(d) = ∏ F(e)^μ(d/e) over the divisors e of d
+        let mut numerator = BigUint::one();
+        let mut denominator = BigUint::one();
+        for ((_, sub_exps), fe) in divisors.iter().zip(&fib_values) {
+            if sub_exps.iter().zip(exps).any(|(e, d)| e > d) {
+                continue;
+            }
+            match mobius(exps, sub_exps) {
+                1 => numerator *= fe,
+                -1 => denominator *= fe,
+                _ => {}
+            }
+        }
+        let primitive = numerator / denominator;
+        if primitive.is_one() {
+            continue;
+        }
+
+        // Aurifeuillian split: P(10k) divides L(5k) for odd k
+        if d % 20 == 10 {
+            let k = d / 10;
+            let l2k = Fib::lucas(2 * k);
+            let five_fk = Fib::single(k) * 5u32;
+            let a = &l2k + 3u32 - &five_fk;
+            let g = primitive.gcd(&a);
+            if !g.is_one() && g != primitive {
+                pieces.push(&primitive / &g);
+                pieces.push(g);
+                continue;
+            }
+        }
+        pieces.push(primitive);
+    }
+    pieces
+}
+
+/// Factor the index n into prime powers, regardless of any budget.
+fn factor_index(mut n: u128) -> Vec<(u128, u32)> {
+    let mut factors: BTreeMap<u128, u32> = BTreeMap::new();
+
+    // Trial division handles every index small enough to actually compute F(n)
+    let mut p = 2;
+    while p * p <= n && p < TRIAL_DIVISION_INDEX_BOUND {
+        while n.is_multiple_of(p) {
+            n /= p;
+            *factors.entry(p).or_default() += 1;
+        }
+        p += if p == 2 { 1 } else { 2 };
+    }
+
+    // Split anything left with rho until only primes remain
+    let mut stack = vec![BigUint::from(n)];
+    while let Some(c) = stack.pop() {
+        if c.is_one() {
+            continue;
+        }
+        if is_probable_prime(&c) {
+            let p = c.to_u128().expect("factor of a u128 fits in u128");
+            *factors.entry(p).or_default() += 1;
+            continue;
+        }
+        let f = (1..)
+            .find_map(|c_rho| pollard_rho(&c, c_rho, u64::MAX))
+            .expect("rho eventually splits a composite");
+        stack.push(&c / &f);
+        stack.push(f);
+    }
+    factors.into_iter().collect()
+}
+
+/// Enumerate the divisors of a number given its prime factorization.
+///
+/// Each divisor is returned with its exponent vector relative to `prime_powers`.
+fn divisors_with_exponents(prime_powers: &[(u128, u32)]) -> Vec<(u128, Vec<u32>)> {
+    let mut divisors = vec![(1u128, Vec::new())];
+    for &(p, max_exp) in prime_powers {
+        divisors = divisors
+            .into_iter()
+            .flat_map(|(d, exps)| {
+                (0..=max_exp).map(move |e| {
+                    let mut exps = exps.clone();
+                    exps.push(e);
+                    (d * p.pow(e), exps)
+                })
+            })
+            .collect();
+    }
+    divisors
+}
+
+/// Compute μ(d/e) from the exponent vectors of d and e.
+fn mobius(d_exps: &[u32], e_exps: &[u32]) -> i32 {
+    let mut result = 1;
+    for (d, e) in d_exps.iter().zip(e_exps) {
+        match d - e {
+            0 => {}
+            1 => result = -result,
+            _ => return 0,
+        }
+    }
+    result
+}
+
+/// Fully factor `m` as far as the budget allows.
+fn factor_cofactor(
+    mut m: BigUint,
+    primes: &[u32],
+    budget: &FactorBudget,
+) -> Vec<(BigUint, FactorKind)> {
+    let mut found = Vec::new();
+
+    // Trial division; stop early once the remaining cofactor is proven prime
+    for &p in primes {
+        if m.is_one() {
+            return found;
+        }
+        if BigUint::from(p).pow(2) > m {
+            found.push((m, FactorKind::Prime));
+            return found;
+        }
+        while (&m % p).is_zero() {
+            m /= p;
+            found.push((BigUint::from(p), FactorKind::Prime));
+        }
+    }
+
+    // Every prime factor of what remains exceeds the trial division bound
+    let proven_bound = BigUint::from(budget.trial_division_bound) + 1u32;
+    let proven_bound = &proven_bound * &proven_bound;
+
+    let mut stack = vec![m];
+    while let Some(c) = stack.pop() {
+        if c.is_one() {
+            continue;
+        }
+        if c < proven_bound {
+            found.push((c, FactorKind::Prime));
+            continue;
+        }
+        if is_probable_prime(&c) {
+            let kind = if c.bits() <= 64 {
+                FactorKind::Prime
+            } else {
+                FactorKind::ProbablePrime
+            };
+            found.push((c, kind));
+            continue;
+        }
+        match find_factor(&c, budget) {
+            Some(f) => {
+                stack.push(&c / &f);
+                stack.push(f);
+            }
+            None => found.push((c, FactorKind::Composite)),
+        }
+    }
+    found
+}
+
+/// Find a non-trivial factor of the composite `n`, or `None` if the budget runs out.
+fn find_factor(n: &BigUint, budget: &FactorBudget) -> Option<BigUint> {
+    let root = n.sqrt();
+    if &root * &root == *n {
+        return Some(root);
+    }
+
+    (1..=3)
+        .find_map(|c| pollard_rho(n, c, budget.rho_iterations / 3))
+        .or_else(|| {
+            (0..budget.ecm_curves).find_map(|curve| ecm(n, 6 + u64::from(curve), budget.ecm_b1))
+        })
+}
+
+/// Brent's variant of Pollard's rho with the polynomial x^2 + c.
+#[allow(clippy::many_single_char_names)] // Mathematical notation: n, c, x, y, q, g, r
+fn pollard_rho(n: &BigUint, c: u64, max_iterations: u64) -> Option<BigUint> {
+    const BATCH: u64 = 128;
+    let f = |x: &BigUint| (x * x + c) % n;
+
+    let mut y = BigUint::from(2u32);
+    let mut x = y.clone();
+    let mut ys = y.clone();
+    let mut q = BigUint::one();
+    let mut g = BigUint::one();
+    let mut r = 1u64;
+    let mut iterations = 0u64;
+
+    while g.is_one() {
+        if iterations >= max_iterations {
+            return None;
+        }
+        x.clone_from(&y);
+        for _ in 0..r {
+            y = f(&y);
+        }
+        let mut k = 0;
+        while k < r && g.is_one() {
+            ys.clone_from(&y);
+            for _ in 0..BATCH.min(r - k) {
+                y = f(&y);
+                q = q * abs_diff(&x, &y) % n;
+            }
+            g = q.gcd(n);
+            k += BATCH;
+        }
+        iterations = iterations.saturating_add(2 * r);
+        r *= 2;
+    }
+
+    // The batched product overshot: retrace the last batch one step at a time
+    if g == *n {
+        loop {
+            ys = f(&ys);
+            g = abs_diff(&x, &ys).gcd(n);
+            if !g.is_one() {
+                break;
+            }
+        }
+    }
+    (g != *n).then_some(g)
+}
+
+/// Lenstra's elliptic curve method on a Montgomery curve from Suyama's parametrization.
+#[allow(clippy::many_single_char_names)] // Mathematical notation: u, v, x, z, n
+fn ecm(n: &BigUint, sigma: u64, b1: u64) -> Option<BigUint> {
+    let b1 = b1.min(FactorBudget::MAX_ECM_B1);
+    let sigma = BigUint::from(sigma);
+    let u = sub_mod(&(&sigma * &sigma % n), &BigUint::from(5u32), n);
+    let v = (&sigma << 2u32) % n;
+    let u3 = u.modpow(&BigUint::from(3u32), n);
+    let v3 = v.modpow(&BigUint::from(3u32), n);
+
+    // a24 = (v - u)^3 * (3u + v) / (16 * u^3 * v)
+    let numerator = sub_mod(&v, &u, n).modpow(&BigUint::from(3u32), n) * ((&u * 3u32 + &v) % n) % n;
+    let denominator = ((&u3 * &v) << 4u32) % n;
+    let a24 = match mod_inverse(&denominator, n) {
+        Ok(inverse) => numerator * inverse % n,
+        Err(g) => return (!g.is_one() && g != *n).then_some(g),
+    };
+    let curve = Curve { n, a24 };
+
+    // Stage 1: multiply by every prime power up to B1
+    let mut point = (u3, v3);
+    for p in primes_up_to(u32::try_from(b1).expect("B1 is capped below 2^32")) {
+        point = curve.mul(largest_power(u64::from(p), b1), &point);
+    }
+    let g = point.1.gcd(n);
+    if g == *n {
+        return None;
+    }
+    if !g.is_one() {
+        return Some(g);
+    }
+
+    // Stage 2: baby-step giant-step continuation up to B2 = 100 * B1
+    let d = 2 * ECM_BABY_STEPS as u64;
+    let b2 = b1.saturating_mul(100);
+    let p2 = curve.dbl(&point);
+    let mut baby = vec![point.clone(), curve.add(&p2, &point, &point)];
+    while baby.len() < ECM_BABY_STEPS {
+        let next = curve.add(&baby[baby.len() - 1], &p2, &baby[baby.len() - 2]);
+        baby.push(next);
+    }
+    // baby[i] holds [2i + 1]P; keep only the multiples coprime to D
+    let baby: Vec<&(BigUint, BigUint)> = baby
+        .iter()
+        .enumerate()
+        .filter(|(i, _)| (2 * *i as u64 + 1).gcd(&d) == 1)
+        .map(|(_, point)| point)
+        .collect();
+
+    let giant_step = curve.mul(d, &point);
+    let first = (b1 / d).max(2);
+    let mut previous = curve.mul((first - 1) * d, &point);
+    let mut current = curve.mul(first * d, &point);
+    let mut accumulator = BigUint::one();
+    for _ in first..=b2 / d + 1 {
+        for (x, z) in &baby {
+            let cross = sub_mod(&(&current.0 * z % n), &(x * &current.1 % n), n);
+            accumulator = accumulator * cross % n;
+        }
+        let next = curve.add(&current, &giant_step, &previous);
+        previous = std::mem::replace(&mut current, next);
+    }
+    let g = accumulator.gcd(n);
+    (!g.is_one() && g != *n).then_some(g)
+}
+
+/// The largest power of `p` not above `bound`, or `p` itself if even that is above it.
+fn largest_power(p: u64, bound: u64) -> u64 {
+    let mut power = p;
+    while let Some(next) = power.checked_mul(p)
+        && next <= bound
+    {
+        power = next;
+    }
+    power
+}
+
+/// A Montgomery curve By^2 = x^3 + Ax^2 + x modulo `n`, using projective x-only coordinates.
+struct Curve<'a> {
+    n: &'a BigUint,
+    /// (A + 2) / 4 reduced modulo n
+    a24: BigUint,
+}
+
+impl Curve<'_> {
+    /// Compute [2]P.
+    fn dbl(&self, (x, z): &(BigUint, BigUint)) -> (BigUint, BigUint) {
+        let n = self.n;
+        let sum = (x + z) % n;
+        let diff = sub_mod(x, z, n);
+        let t1 = &sum * &sum % n;
+        let t2 = &diff * &diff % n;
+        let t3 = sub_mod(&t1, &t2, n);
+        let x2 = &t1 * &t2 % n;
+        let z2 = &t3 * ((&t2 + &self.a24 * &t3) % n) % n;
+        (x2, z2)
+    }
+
+    /// Compute P + Q given P - Q.
+    fn add(
+        &self,
+        (xp, zp): &(BigUint, BigUint),
+        (xq, zq): &(BigUint, BigUint),
+        (xd, zd): &(BigUint, BigUint),
+    ) -> (BigUint, BigUint) {
+        let n = self.n;
+        let u = sub_mod(xp, zp, n) * ((xq + zq) % n) % n;
+        let v = ((xp + zp) % n) * sub_mod(xq, zq, n) % n;
+        let sum = (&u + &v) % n;
+        let diff = sub_mod(&u, &v, n);
+        (zd * (&sum * &sum % n) % n, xd * (&diff * &diff % n) % n)
+    }
+
+    /// Compute [k]P with a Montgomery ladder.
+    fn mul(&self, k: u64, point: &(BigUint, BigUint)) -> (BigUint, BigUint) {
+        let mut r0 = point.clone();
+        let mut r1 = self.dbl(point);
+        for bit in (0..k.ilog2()).rev() {
+            if k >> bit & 1 == 1 {
+                r0 = self.add(&r1, &r0, point);
+                r1 = self.dbl(&r1);
+            } else {
+                r1 = self.add(&r1, &r0, point);
+                r0 = self.dbl(&r0);
+            }
+        }
+        r0
+    }
+}
+
+/// Combine the factors found in every piece, dividing known primes out of composites.
+fn merge_factors(found: Vec<(BigUint, FactorKind)>) -> Vec<Factor> {
+    let mut primes: BTreeMap<BigUint, (u32, FactorKind)> = BTreeMap::new();
+    let mut composites = Vec::new();
+    for (value, kind) in found {
+        if kind == FactorKind::Composite {
+            composites.push(value);
+        } else {
+            primes.entry(value).or_insert((0, kind)).0 += 1;
+        }
+    }
+
+    // The same prime can divide several primitive parts (e.g. intrinsic factors)
+    let mut remaining: BTreeMap<BigUint, u32> = BTreeMap::new();
+    for mut composite in composites {
+        for (p, (exponent, _)) in &mut primes {
+            while (&composite % p).is_zero() {
+                composite /= p;
+                *exponent += 1;
+            }
+        }
+        if !composite.is_one() {
+            *remaining.entry(composite).or_default() += 1;
+        }
+    }
+
+    let mut factors: Vec<Factor> = primes
+        .into_iter()
+        .map(|(value, (exponent, kind))| Factor {
+            value,
+            exponent,
+            kind,
+        })
+        .chain(remaining.into_iter().map(|(value, exponent)| Factor {
+            value,
+            exponent,
+            kind: FactorKind::Composite,
+        }))
+        .collect();
+    factors.sort_by(|a, b| a.kind.cmp(&b.kind).then_with(|| a.value.cmp(&b.value)));
+    factors
+}
+
+/// Sieve of Eratosthenes returning all primes up to and including `bound`.
+fn primes_up_to(bound: u32) -> Vec<u32> {
+    let bound = bound as usize;
+    if bound < 2 {
+        return Vec::new();
+    }
+    let mut is_composite = vec![false; bound + 1];
+    let mut primes = Vec::new();
+    for i in 2..=bound {
+        if is_composite[i] {
+            continue;
+        }
+        primes.push(u32::try_from(i).expect("bound fits in u32"));
+        for multiple in (i * i..=bound).step_by(i) {
+            is_composite[multiple] = true;
+        }
+    }
+    primes
+}
+
+/// Compute `|a - b|`.
+fn abs_diff(a: &BigUint, b: &BigUint) -> BigUint {
+    if a >= b { a - b } else { b - a }
+}
+
+/// Compute `(a - b) mod n` for `a, b < n`.
+fn sub_mod(a: &BigUint, b: &BigUint, n: &BigUint) -> BigUint {
+    if a >= b { a - b } else { n - b + a }
+}
+
+/// Compute the inverse of `a` modulo `n`, or return `gcd(a, n)` if it does not exist.
+fn mod_inverse(a: &BigUint, n: &BigUint) -> Result<BigUint, BigUint> {
+    let modulus = BigInt::from(n.clone());
+    let extended = BigInt::from(a % n).extended_gcd(&modulus);
+    if extended.gcd.is_one() {
+        Ok(extended.x.mod_floor(&modulus).magnitude().clone())
+    } else {
+        Err(extended.gcd.magnitude().clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn product(factorization: &Factorization) -> BigUint {
+        factorization
+            .factors()
+            .iter()
+            .map(|f| f.value.pow(f.exponent))
+            .product()
+    }
+
+    #[test]
+    fn small_factorizations() {
+        assert_eq!(Fib::factor(6).to_string(), "F(6) = 2^3");
+        assert_eq!(Fib::factor(10).to_string(), "F(10) = 5 * 11");
+        assert_eq!(Fib::factor(7).to_string(), "F(7) = 13");
+        assert_eq!(Fib::factor(2).to_string(), "F(2) = 1");
+        assert!(Fib::factor(0).factors().is_empty());
+    }
+
+    #[test]
+    fn products_match_fibonacci_numbers() {
+        for n in 3..=100 {
+            let factorization = Fib::factor(n);
+            assert_eq!(product(&factorization), Fib::single(n), "F({n})");
+            assert!(factorization.is_complete(), "F({n})");
+        }
+    }
+
+    #[test]
+    fn aurifeuillian_split() {
+        // P(50) = 101 * 151 is split algebraically even with no trial division at all
+        let budget = FactorBudget {
+            trial_division_bound: 0,
+            rho_iterations: 0,
+            ecm_curves: 0,
+            ecm_b1: 0,
+        };
+        let factorization = Fib::factor_with_budget(50, &budget);
+        assert_eq!(product(&factorization), Fib::single(50));
+        for p in [101u32, 151] {
+            assert!(
+                factorization
+                    .factors()
+                    .iter()
+                    .any(|f| f.value == BigUint::from(p))
+            );
+        }
+    }
+
+    #[test]
+    fn unfactored_composites_are_reported() {
+        let budget = FactorBudget {
+            trial_division_bound: 100,
+            rho_iterations: 0,
+            ecm_curves: 0,
+            ecm_b1: 0,
+        };
+        // P(97) = F(97) = 193 * 389 * 3084989 * 361040209
+        let factorization = Fib::factor_with_budget(97, &budget);
+        assert!(!factorization.is_complete());
+        assert_eq!(product(&factorization), Fib::single(97));
+    }
+
+    #[test]
+    fn generic_methods_find_factors() {
+        // 1000003 * (2^89 - 1)
+        let p = BigUint::from(1_000_003u32);
+        let n = &p * ((BigUint::one() << 89) - 1u32);
+        assert_eq!(
+            (6..40).find_map(|sigma| ecm(&n, sigma, 1_000)),
+            Some(p.clone())
+        );
+        assert_eq!(pollard_rho(&n, 1, 100_000), Some(p));
+    }
+
+    #[test]
+    fn ecm_bounds_do_not_overflow() {
+        assert_eq!(largest_power(2, 1_000), 512);
+        assert_eq!(largest_power(7, 5), 7);
+        let p = 4_294_967_291;
+        assert_eq!(largest_power(p, u64::MAX), p * p);
+        assert_eq!(largest_power(2, u64::MAX), 1 << 63);
+    }
+
+    #[test]
+    fn index_factorization() {
+        assert_eq!(factor_index(1), vec![]);
+        assert_eq!(factor_index(360), vec![(2, 3), (3, 2), (5, 1)]);
+        let large = (1u128 << 61) - 1;
+        assert_eq!(factor_index(large * 3), vec![(3, 1), (large, 1)]);
+    }
+}
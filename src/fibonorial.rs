@@ -0,0 +1,198 @@
+//! Fibonorials and Fibonomial coefficients.
+//!
+//! The fibonorial n!_F = F(1) * F(2) * ... * F(n) is the Fibonacci analogue of the factorial,
+//! and the Fibonomial coefficient C(n, k) = n!_F / (k!_F * (n-k)!_F) is always an integer.
+//!
+//! Products are formed from the consecutive values generated by [`Fib::range`] using a product
+//! tree split by bit length rather than by count, so both halves of every multiplication are
+//! roughly the same size, and independent subtrees are multiplied in parallel.
+
+use num_bigint::BigUint;
+use num_traits::{One, Zero};
+use rayon::join;
+
+use crate::Fib;
+
+/// Below this many factors a product is computed sequentially.
+const SEQUENTIAL_PRODUCT_LEN: usize = 16;
+
+impl Fib {
+    /// Calculate the fibonorial F(1) * F(2) * ... * F(n).
+    ///
+    /// # Arguments
+    ///
+    /// * `n` - The number of Fibonacci factors in the product
+    ///
+    /// # Returns
+    ///
+    /// * The fibonorial as a `BigUint` (the empty product 1 for `n = 0`)
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use fib_rs::Fib;
+    /// use num_bigint::BigUint;
+    ///
+    /// // 1 * 1 * 2 * 3 * 5 * 8
+    /// assert_eq!(Fib::fibonorial(6), BigUint::from(240u32));
+    /// ```
+    #[must_use]
+    pub fn fibonorial(n: u128) -> BigUint {
+        if n == 0 {
+            return BigUint::one();
+        }
+        balanced_product(&Self::range(1, n))
+    }
+
+    /// Calculate the Fibonomial coefficient C(n, k).
+    ///
+    /// Computed as F(n-k+1) * ... * F(n) divided by k!_F, where k is replaced by n - k when that
+    /// is smaller. Numerator and denominator are built in parallel with balanced product trees.
+    ///
+    /// # Arguments
+    ///
+    /// * `n` - The row of the Fibonomial triangle
+    /// * `k` - The position within the row
+    ///
+    /// # Returns
+    ///
+    /// * The Fibonomial coefficient as a `BigUint`, or zero if `k > n`
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use fib_rs::Fib;
+    /// use num_bigint::BigUint;
+    ///
+    /// // (F(6) * F(5) * F(4)) / (F(1) * F(2) * F(3)) = 8 * 5 * 3 / 2
+    /// assert_eq!(Fib::fibonomial(6, 3), BigUint::from(60u32));
+    /// assert_eq!(Fib::fibonomial(3, 6), BigUint::from(0u32));
+    /// ```
+    #[must_use]
+    pub fn fibonomial(n: u128, k: u128) -> BigUint {
+        if k > n {
+            return BigUint::zero();
+        }
+        let k = k.min(n - k);
+        if k == 0 {
+            return BigUint::one();
+        }
+        let (numerator, denominator) = join(
+            || balanced_product(&Self::range(n - k + 1, n)),
+            || Self::fibonorial(k),
+        );
+        numerator / denominator
+    }
+
+    /// Calculate the full row n of the Fibonomial triangle, C(n, 0) through C(n, n).
+    ///
+    /// Uses the recurrence C(n, k) = C(n, k-1) * F(n-k+1) / F(k) for the first half of the
+    /// row and mirrors it for the second half.
+    ///
+    /// # Arguments
+    ///
+    /// * `n` - The row of the Fibonomial triangle
+    ///
+    /// # Returns
+    ///
+    /// * A `Vec<BigUint>` with the n + 1 coefficients of the row
+    ///
+    /// # Panics
+    ///
+    /// Panics if the row length does not fit in `usize`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use fib_rs::Fib;
+    /// use num_bigint::BigUint;
+    ///
+    /// let row: Vec<BigUint> = [1u32, 5, 15, 15, 5, 1].into_iter().map(BigUint::from).collect();
+    /// assert_eq!(Fib::fibonomial_row(5), row);
+    /// ```
+    #[must_use]
+    pub fn fibonomial_row(n: u128) -> Vec<BigUint> {
+        let len = n
+            .checked_add(1)
+            .and_then(|len| usize::try_from(len).ok())
+            .expect("row length fits in usize");
+        let fibs = Self::range(0, n);
+
+        let mut row = Vec::with_capacity(len);
+        row.push(BigUint::one());
+        for k in 1..=len / 2 {
+            let next = &row[k - 1] * &fibs[len - k] / &fibs[k];
+            row.push(next);
+        }
+        for k in row.len()..len {
+            row.push(row[len - 1 - k].clone());
+        }
+        row
+    }
+}
+
+/// Multiply all values using a product tree balanced by bit length.
+fn balanced_product(values: &[BigUint]) -> BigUint {
+    if values.len() <= SEQUENTIAL_PRODUCT_LEN {
+        return values.iter().product();
+    }
+
+    // Split where the running bit count reaches half of the total
+    let total_bits: u64 = values.iter().map(BigUint::bits).sum();
+    let mut running = 0;
+    let split = values
+        .iter()
+        .position(|v| {
+            running += v.bits();
+            running * 2 >= total_bits
+        })
+        .map_or(values.len() / 2, |i| i + 1)
+        .clamp(1, values.len() - 1);
+
+    let (left, right) = values.split_at(split);
+    let (left, right) = join(|| balanced_product(left), || balanced_product(right));
+    left * right
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fibonorial_values() {
+        let expected = [1u32, 1, 1, 2, 6, 30, 240, 3120, 65_520];
+        for (n, value) in (0..).zip(expected) {
+            assert_eq!(Fib::fibonorial(n), BigUint::from(value));
+        }
+        // Large enough to exercise the parallel product tree
+        let sequential: BigUint = Fib::range(1, 500).iter().product();
+        assert_eq!(Fib::fibonorial(500), sequential);
+    }
+
+    #[test]
+    fn fibonomial_matches_definition() {
+        for n in 0..40 {
+            for k in 0..=n {
+                let expected = Fib::fibonorial(n) / (Fib::fibonorial(k) * Fib::fibonorial(n - k));
+                assert_eq!(Fib::fibonomial(n, k), expected, "C({n}, {k})");
+            }
+        }
+    }
+
+    #[test]
+    fn rows_match_coefficients() {
+        for n in [0, 1, 2, 7, 30, 101] {
+            let row = Fib::fibonomial_row(n);
+            assert_eq!(row.len() as u128, n + 1);
+            for (k, value) in (0..).zip(&row) {
+                assert_eq!(*value, Fib::fibonomial(n, k), "C({n}, {k})");
+            }
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "row length fits in usize")]
+    fn oversized_row_panics() {
+        let _ = Fib::fibonomial_row(u128::MAX);
+    }
+}
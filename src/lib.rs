@@ -9,6 +9,28 @@
 //! - **Handles massive inputs**: Compute Fibonacci numbers up to F(10,000,000) and beyond
 //! - **Range calculation**: Generate sequences of consecutive Fibonacci numbers with parallel processing
 //! - **`BigUint` support**: Uses arbitrary precision integers for handling large Fibonacci numbers
+//! - **Primality testing**: Baillie–PSW probable-prime test built on Lucas-sequence doubling
+//! - **Factorization**: Splits F(n) along its algebraic structure before trial division, Pollard rho and ECM
+//! - **Range aggregates**: Closed-form sums over index intervals at the cost of a few single computations
+//! - **Fibonorials**: Fibonorials and Fibonomial coefficients built with parallel balanced product trees
+//! - **Constants**: φ, 1/φ, √5 and the reciprocal Fibonacci constant to any number of verified digits
+//! - **Approximation**: Digit count and leading digits of F(n) for astronomically large n
+//! - **Exact sizes**: Bit and decimal lengths of F(n) without computing it
+//! - **Memory guard**: Predicts output size and peak memory and refuses requests over a limit
+//! - **Fallible API**: `try_` variants reporting inverted ranges, oversized counts and limits as `FibError`
+//! - **Cancellation**: `single_with` and `range_with` observe a cancellation token, a deadline and a progress callback
+//! - **Configurable engine**: `FibEngine` carries its own thread pool, chunking policy, algorithm and memory limit
+//! - **Selectable algorithms**: Naive iteration, matrix powering, fast doubling and Lucas doubling for cross-checks
+//! - **Verification**: Checks a claimed F(n) against residues modulo random word-sized primes, optionally on every result
+//! - **Parallel multiplication**: Runs the two squarings of each doubling step concurrently and splits huge products across threads
+//! - **NTT multiplication**: Multiplies multi-million-bit operands with three-prime number-theoretic transforms in O(n log n)
+//! - **Pluggable backends**: `single_in` and `try_range_in` compute in any `Backend`, with `malachite` and `ibig` implementations behind cargo features
+//! - **Primitive results**: `single_u64`, `single_u128` and `checked_single` look values up in a compile-time table, which also serves small indices everywhere
+//! - **Contiguous ranges**: `FibRange` stores a whole range in one limb buffer with borrowed views
+//! - **Parallel iteration**: `par_range` exposes a range as a rayon `IndexedParallelIterator` of `(n, F(n))`
+//! - **Streaming output**: `write_range` writes a range to any `io::Write` in order with bounded memory
+//! - **Decimal conversion**: `to_decimal` and `write_decimal` format huge values in subquadratic time
+//! - **Out-of-core mode**: `single_to_file` keeps operands on disk under a memory cap for results larger than RAM
 //!
 //! ## Examples
 //!
@@ -34,33 +56,87 @@
 //! ### Single Fibonacci Number
 //!
 //! For computing a single Fibonacci number, this implementation uses the fast doubling algorithm
-//! with logarithmic time complexity:
+//! with logarithmic time complexity. The bits of n are scanned from the most significant, keeping
+//! (F(k), F(k-1)) and doubling k with two squarings per step:
 //!
-//! - For even n: F(2k) = F(k) * (2*F(k+1) - F(k))
-//! - For odd n:  F(2k+1) = F(k+1)^2 + F(k)^2
+//! - F(2k-1) = F(k)^2 + F(k-1)^2
+//! - F(2k+1) = 4*F(k)^2 - F(k-1)^2 + 2(-1)^k
+//! - F(2k) = F(2k+1) - F(2k-1)
+//!
+//! The last step computes only F(n), with a single multiplication.
+//!
+//! On a multi-threaded pool, the two squarings of each step run concurrently and very large
+//! products are split Karatsuba-style into sub-products computed in parallel.
+//! Products of operands above about four million bits use a number-theoretic transform modulo
+//! three 62-bit primes, combined with the Chinese remainder theorem, instead of Toom-3.
 //!
 //! ### Fibonacci Range
 //!
 //! The range implementation combines three approaches for optimal performance:
 //!
-//! 1. **Parallel processing**: Divides the requested range into optimal chunks based on available CPU threads
-//! 2. **Smart initialization**: Uses the fast doubling algorithm to efficiently find the starting values for each chunk
+//! 1. **Parallel processing**: Divides the requested range into several chunks per CPU thread, sized so each holds about the same number of bits, and lets idle threads steal the remaining chunks
+//! 2. **Smart initialization**: Uses the fast doubling algorithm to find the starting values of the first chunk, and the addition formula F(m+n) = F(m)F(n-1) + F(m+1)F(n) to step from each chunk to the next
 //! 3. **Iterative calculation**: After finding starting values, computes subsequent Fibonacci numbers iteratively within each chunk
 
+mod algorithm;
+mod approx;
+mod arena;
+mod backend;
+pub mod constants;
+mod control;
+mod decimal;
+mod disk;
+mod engine;
+mod error;
+mod estimate;
+mod factor;
+mod fibonorial;
+mod modular;
+mod mul;
+mod ntt;
+mod par;
+mod primality;
+mod small;
+mod sums;
+mod verify;
+mod write;
+
+pub use algorithm::{Algorithm, ParseAlgorithmError};
+pub use approx::FibApprox;
+pub use arena::{FibRange, FibRangeIter, FibView};
+pub use backend::Backend;
+pub use control::{CancellationToken, Control, Progress};
+pub use decimal::{to_decimal, write_decimal};
+pub use disk::{FileFormat, OutOfCore};
+pub use engine::{Chunking, FibEngine, FibEngineBuilder};
+pub use error::FibError;
+pub use estimate::{Estimate, LimitExceeded, MemoryGuard};
+pub use factor::{Factor, FactorBudget, FactorKind, Factorization};
+pub use par::FibParRange;
+pub use primality::is_probable_prime;
+pub use write::RangeFormat;
+
 use std::{
-    cmp::{max, min},
+    convert::Infallible,
     iter::from_fn,
     mem::{replace, take},
+    sync::atomic::{AtomicU64, Ordering},
 };
 
 use num_bigint::BigUint;
-use num_traits::{One, Zero};
-use rayon::{current_num_threads, prelude::*};
+use rayon::prelude::*;
 
 /// Type alias for the result of the fast doubling algorithm
 ///
 /// Represents a pair of consecutive Fibonacci numbers (F(n), F(n+1))
-type FibPair = (BigUint, BigUint);
+type FibPair<B = BigUint> = (B, B);
+
+/// A range chunk is seeded from its neighbour when it is at least this many times further from
+/// index zero than from the neighbour's start, and with fast doubling otherwise.
+const NEIGHBOUR_SEED_RATIO: u128 = 16;
+
+/// Number of values generated by a range chunk between checks of its `Control`.
+const CONTROL_CHECK_INTERVAL: usize = 64;
 
 /// A utility struct for computing Fibonacci numbers efficiently.
 ///
@@ -72,7 +148,10 @@ type FibPair = (BigUint, BigUint);
 /// and leverages parallel processing for range calculations to maximize performance.
 ///
 /// Uses `BigUint` for arbitrary precision, ensuring correct results for extremely large
-/// Fibonacci numbers.
+/// Fibonacci numbers. [`Fib::single_in`] and [`Fib::try_range_in`] compute in any other
+/// [`Backend`] instead.
+///
+/// These methods use the global rayon pool; see [`FibEngine`] for a configurable alternative.
 pub struct Fib;
 
 impl Fib {
@@ -91,8 +170,8 @@ impl Fib {
     ///
     /// # Complexity
     ///
-    /// * Time complexity: O(log n) due to the fast doubling algorithm
-    /// * Space complexity: O(log n) for the recursive call stack
+    /// * Time complexity: O(log n) doubling steps, each costing two squarings
+    /// * Space complexity: O(1) beyond the numbers themselves, as the bits of n are scanned iteratively
     ///
     /// # Examples
     ///
@@ -107,22 +186,48 @@ impl Fib {
     /// ```
     #[must_use]
     pub fn single(n: u128) -> BigUint {
-        match n {
-            0 => BigUint::zero(),
-            1 => BigUint::one(),
-            _ => Self::fib_fast_doubling_helper(n).0,
-        }
+        Self::single_in(n)
     }
 
-    /// Helper function for the fast doubling algorithm.
+    /// Calculate the nth Fibonacci number, observing a cancellation token, a deadline and a
+    /// progress callback.
+    ///
+    /// Progress is reported after every doubling step, of which there is one per bit of `n`,
+    /// and the controls are checked before and after every step.
+    ///
+    /// # Arguments
     ///
-    /// This function implements the recursive divide-and-conquer approach for computing
-    /// Fibonacci numbers using the fast doubling method. It returns a pair of consecutive
-    /// Fibonacci numbers (F(n), F(n+1)) to enable the recursion.
+    /// * `n` - The index of the Fibonacci number to calculate
+    /// * `control` - The cancellation token, deadline and progress callback to observe
     ///
-    /// The algorithm is based on the following mathematical identities:
-    /// - For even n: F(2k) = F(k) * (2*F(k+1) - F(k))
-    /// - For odd n:  F(2k+1) = F(k+1)^2 + F(k)^2
+    /// # Returns
+    ///
+    /// * The nth Fibonacci number as a `BigUint`
+    ///
+    /// # Errors
+    ///
+    /// * `FibError::Cancelled` if the token is cancelled before the computation completes
+    /// * `FibError::DeadlineExceeded` if the deadline passes before the computation completes
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::time::Instant;
+    /// use fib_rs::{Control, Fib, FibError};
+    ///
+    /// assert_eq!(Fib::single_with(10, &Control::new()).unwrap(), Fib::single(10));
+    ///
+    /// let expired = Control::new().deadline(Instant::now());
+    /// assert_eq!(Fib::single_with(1_000_000, &expired), Err(FibError::DeadlineExceeded));
+    /// ```
+    pub fn single_with(n: u128, control: &Control) -> Result<BigUint, FibError> {
+        Algorithm::FastDoubling.single_with(n, control)
+    }
+
+    /// Helper function for the fast doubling algorithm.
+    ///
+    /// Returns the pair of consecutive Fibonacci numbers (F(n), F(n+1)), which is what range
+    /// chunks and other derived computations need as a starting point.
     ///
     /// # Arguments
     ///
@@ -134,43 +239,169 @@ impl Fib {
     ///
     /// # Time Complexity
     ///
-    /// * O(log n) due to the recursive divide-and-conquer approach
-    #[allow(clippy::similar_names)] // Mathematical notation: F(k), F(k+1), F(2k), F(2k+1)
-    fn fib_fast_doubling_helper(n: u128) -> FibPair {
-        if n == 0 {
-            return (BigUint::zero(), BigUint::one());
+    /// * O(log n) doubling steps, each costing two squarings
+    pub(crate) fn fib_fast_doubling_helper(n: u128) -> FibPair {
+        let Ok(pair) = Self::fib_fast_doubling_steps(n, &mut || Ok::<_, Infallible>(()));
+        pair
+    }
+
+    /// Compute the pair (F(n), F(n+1)) with a hook called after every doubling step.
+    ///
+    /// The hook runs once per bit of `n`; returning an error from it abandons the computation.
+    pub(crate) fn fib_fast_doubling_steps<B, E, F>(n: u128, step: &mut F) -> Result<FibPair<B>, E>
+    where
+        B: Backend,
+        F: FnMut() -> Result<(), E>,
+    {
+        if let Some(pair) = small::pair(n) {
+            return Ok(pair);
         }
+        // F(n+1) = F(n) + F(n-1), formed in the buffer of F(n-1)
+        let (current, mut next) = Self::fib_doubling_prefix::<B, E, F>(n, step)?;
+        next.add_assign(&current);
+        Ok((current, next))
+    }
 
-        // Calculate F(k) and F(k+1) where k = floor(n/2)
-        let (fk, fk1) = Self::fib_fast_doubling_helper(n / 2);
+    /// Advance the pair (F(s), F(s+1)) by `d` indices with the addition formula:
+    /// - F(s+d) = F(s)*F(d-1) + F(s+1)*F(d)
+    /// - F(s+d+1) = F(s)*F(d) + F(s+1)*F(d+1)
+    ///
+    /// When `d` is much smaller than `s`, the factors F(d-1), F(d) and F(d+1) are small and the
+    /// four unbalanced products cost far less than computing the new pair from scratch.
+    #[allow(clippy::similar_names)] // Mathematical notation: F(s), F(s+1), F(d-1), F(d), F(d+1)
+    fn fib_advance<B: Backend>((fs, fs1): &FibPair<B>, d: u128) -> FibPair<B> {
+        let Ok((fd, fd1)) =
+            Self::fib_fast_doubling_steps::<B, _, _>(d, &mut || Ok::<_, Infallible>(()));
+        // F(d-1) = F(d+1) - F(d)
+        let mut fd_1 = fd1.clone();
+        fd_1.sub_assign(&fd);
+
+        let mut next = fs.mul(&fd_1);
+        next.add_assign(&fs1.mul(&fd));
+        let mut after = fs.mul(&fd);
+        after.add_assign(&fs1.mul(&fd1));
+        (next, after)
+    }
 
-        // Calculate F(2k) and F(2k+1)
-        let two_fk1 = &fk1 << 1; // Efficiently multiply by 2 using bit shift
-        let term = &two_fk1 - &fk;
-        let f2k = &fk * &term; // F(2k) = F(k) * (2*F(k+1) - F(k))
-        let f2k1 = &fk * &fk + &fk1 * &fk1; // F(2k+1) = F(k)^2 + F(k+1)^2
+    /// Compute F(n) alone with a hook called after every doubling step.
+    ///
+    /// The last step only needs F(n), which takes a single multiplication instead of the two
+    /// squarings that would also produce its neighbour:
+    /// - F(2k) = F(k) * (F(k) + 2*F(k-1))
+    /// - F(2k+1) = (2*F(k) + F(k-1)) * (2*F(k) - F(k-1)) + 2(-1)^k
+    ///
+    /// The hook runs once per bit of `n`; returning an error from it abandons the computation.
+    #[allow(clippy::similar_names)] // Mathematical notation: F(k), F(k-1)
+    pub(crate) fn fib_single_steps<B, E, F>(n: u128, step: &mut F) -> Result<B, E>
+    where
+        B: Backend,
+        F: FnMut() -> Result<(), E>,
+    {
+        let k = n >> 1;
+        match n {
+            0 => return Ok(B::from_u32(0)),
+            1 => {
+                step()?;
+                return Ok(B::from_u32(1));
+            }
+            _ => {}
+        }
 
-        // Return appropriate pair based on whether n is even or odd
-        // Already internally does a bitwise operation
-        if n.is_multiple_of(2) {
-            (f2k, f2k1)
+        let (mut fk, mut fk_1) = Self::fib_doubling_prefix::<B, E, F>(k, step)?;
+        let value = if n.is_multiple_of(2) {
+            // F(k) + 2*F(k-1), formed in the buffer of F(k-1)
+            fk_1.mul_u32(2);
+            fk_1.add_assign(&fk);
+            fk.mul(&fk_1)
         } else {
-            let f2k2 = &f2k1 + &f2k;
-            (f2k1, f2k2)
+            // 2*F(k) + F(k-1) in the buffer of F(k-1), then 2*F(k) - F(k-1) = 4*F(k) - that
+            fk.mul_u32(2);
+            fk_1.add_assign(&fk);
+            fk.mul_u32(2);
+            fk.sub_assign(&fk_1);
+            let mut product = fk.mul(&fk_1);
+            if k.is_multiple_of(2) {
+                product.add_u32(2);
+            } else {
+                product.sub_u32(2);
+            }
+            product
+        };
+        step()?;
+        Ok(value)
+    }
+
+    /// Compute (F(k), F(k-1)) for k >= 1 by scanning the bits of k from the most significant.
+    ///
+    /// Each doubling step from k to 2k or 2k+1 costs two squarings:
+    /// - F(2k-1) = F(k)^2 + F(k-1)^2
+    /// - F(2k+1) = 4*F(k)^2 - F(k-1)^2 + 2(-1)^k
+    /// - F(2k) = F(2k+1) - F(2k-1)
+    ///
+    /// The hook runs once per bit of `k`, the leading bit included.
+    #[allow(clippy::similar_names)] // Mathematical notation: F(k), F(k-1), F(2k-1), F(2k+1)
+    fn fib_doubling_prefix<B, E, F>(k: u128, step: &mut F) -> Result<FibPair<B>, E>
+    where
+        B: Backend,
+        F: FnMut() -> Result<(), E>,
+    {
+        debug_assert!(k > 0);
+        // Start from the leading bit: (F(1), F(0))
+        let (mut fk, mut fk_1) = (B::from_u32(1), B::from_u32(0));
+        let mut k_is_odd = true;
+        step()?;
+
+        for bit in (0..u128::BITS - 1 - k.leading_zeros()).rev() {
+            // Products need fresh storage, which replaces the buffers of F(k) and F(k-1)
+            // The two squarings are independent and run concurrently once they are large
+            (fk, fk_1) = B::square_pair(&fk, &fk_1);
+
+            // Everything else happens in place in those two buffers:
+            // F(2k-1) = F(k)^2 + F(k-1)^2
+            fk_1.add_assign(&fk);
+            // F(2k+1) = 4*F(k)^2 - F(k-1)^2 + 2(-1)^k = 5*F(k)^2 - F(2k-1) + 2(-1)^k
+            fk.mul_u32(5);
+            fk.sub_assign(&fk_1);
+            if k_is_odd {
+                fk.sub_u32(2);
+            } else {
+                fk.add_u32(2);
+            }
+
+            // Keep (F(2k+1), F(2k)) for a set bit and (F(2k), F(2k-1)) otherwise,
+            // with F(2k) = F(2k+1) - F(2k-1) written over the value that is dropped
+            k_is_odd = k >> bit & 1 == 1;
+            if k_is_odd {
+                fk_1.sub_from(&fk);
+            } else {
+                fk.sub_assign(&fk_1);
+            }
+            step()?;
         }
+        Ok((fk, fk_1))
+    }
+
+    /// Calculate the nth Lucas number L(n) = F(n-1) + F(n+1).
+    ///
+    /// Computed as L(n) = 2*F(n+1) - F(n) from a single fast doubling call.
+    pub(crate) fn lucas(n: u128) -> BigUint {
+        let (fk, fk1) = Self::fib_fast_doubling_helper(n);
+        (fk1 << 1) - fk
     }
 
     /// Generates Fibonacci numbers for indices in the given inclusive range.
     ///
     /// This method efficiently computes a sequence of consecutive Fibonacci numbers
     /// using parallel processing for improved performance. It divides the requested range
-    /// into optimal chunks based on the available CPU threads, calculates each chunk in
-    /// parallel, and then combines the results.
+    /// into chunks of about equal total bit length, several per CPU thread, calculates the
+    /// chunks in parallel, and then combines the results.
     ///
     /// The implementation uses a hybrid approach that:
-    /// 1. Uses the fast doubling algorithm to efficiently find the starting values for each chunk
+    /// 1. Uses the fast doubling algorithm to find the starting values of the first chunk, and
+    ///    the addition formula to step from each chunk's starting values to the next
     /// 2. Computes subsequent Fibonacci numbers iteratively within each chunk
-    /// 3. Processes chunks in parallel using the Rayon library
+    /// 3. Processes chunks in parallel using the Rayon library, whose work stealing keeps every
+    ///    thread busy until the last chunk
     ///
     /// # Arguments
     ///
@@ -215,45 +446,142 @@ impl Fib {
     /// let large_range = Fib::range(1000, 10999);
     /// assert_eq!(large_range.len(), 10000);
     /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if the number of values in the range does not fit in `usize`. Use
+    /// [`Fib::try_range`] to handle this case, and inverted ranges, as errors.
     #[must_use]
     pub fn range(start: u128, end: u128) -> Vec<BigUint> {
-        // Validate input range
-        if end < start {
-            return Vec::new();
+        match Self::try_range(start, end) {
+            Ok(values) => values,
+            Err(FibError::InvertedRange { .. }) => Vec::new(),
+            Err(error) => panic!("{error}"),
         }
+    }
+
+    /// Generates Fibonacci numbers for indices in the given inclusive range, reporting invalid
+    /// ranges as errors.
+    ///
+    /// Behaves like [`Fib::range`], but distinguishes an inverted range from an empty result and
+    /// refuses ranges whose length does not fit in `usize` instead of truncating it.
+    ///
+    /// # Arguments
+    ///
+    /// * `start` - The starting index of the range
+    /// * `end` - The ending index of the range (inclusive)
+    ///
+    /// # Returns
+    ///
+    /// * A `Vec<BigUint>` containing ordered Fibonacci numbers for indices in the specified inclusive range.
+    ///
+    /// # Errors
+    ///
+    /// * `FibError::InvertedRange` if `end < start`
+    /// * `FibError::CountOverflow` if the number of values does not fit in `usize`
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use fib_rs::{Fib, FibError};
+    ///
+    /// assert_eq!(Fib::try_range(3, 10).unwrap().len(), 8);
+    /// assert_eq!(
+    ///     Fib::try_range(10, 3),
+    ///     Err(FibError::InvertedRange { start: 10, end: 3 })
+    /// );
+    /// assert!(matches!(
+    ///     Fib::try_range(0, u128::MAX),
+    ///     Err(FibError::CountOverflow { .. })
+    /// ));
+    /// ```
+    pub fn try_range(start: u128, end: u128) -> Result<Vec<BigUint>, FibError> {
+        Self::range_with(start, end, &Control::new())
+    }
 
+    /// Generates Fibonacci numbers for indices in the given inclusive range, observing a
+    /// cancellation token, a deadline and a progress callback.
+    ///
+    /// Progress is reported whenever a parallel chunk of the range completes. The controls are
+    /// checked while seeding each chunk and periodically while it is generated; once one chunk
+    /// stops, the remaining chunks stop as well.
+    ///
+    /// # Arguments
+    ///
+    /// * `start` - The starting index of the range
+    /// * `end` - The ending index of the range (inclusive)
+    /// * `control` - The cancellation token, deadline and progress callback to observe
+    ///
+    /// # Returns
+    ///
+    /// * A `Vec<BigUint>` containing ordered Fibonacci numbers for indices in the specified inclusive range.
+    ///
+    /// # Errors
+    ///
+    /// * `FibError::InvertedRange` if `end < start`
+    /// * `FibError::CountOverflow` if the number of values does not fit in `usize`
+    /// * `FibError::Cancelled` if the token is cancelled before the computation completes
+    /// * `FibError::DeadlineExceeded` if the deadline passes before the computation completes
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::sync::atomic::{AtomicU64, Ordering};
+    /// use fib_rs::{CancellationToken, Control, Fib, FibError, Progress};
+    ///
+    /// let chunks = AtomicU64::new(0);
+    /// let report = |_: Progress| {
+    ///     chunks.fetch_add(1, Ordering::Relaxed);
+    /// };
+    /// let values = Fib::range_with(0, 1_000, &Control::new().on_progress(&report)).unwrap();
+    /// assert_eq!(values, Fib::range(0, 1_000));
+    /// assert!(chunks.load(Ordering::Relaxed) > 0);
+    ///
+    /// let token = CancellationToken::new();
+    /// token.cancel();
+    /// let cancelled = Control::new().cancel_with(token);
+    /// assert_eq!(Fib::range_with(0, 1_000, &cancelled), Err(FibError::Cancelled));
+    /// ```
+    pub fn range_with(start: u128, end: u128, control: &Control) -> Result<Vec<BigUint>, FibError> {
+        Self::range_chunked(start, end, Chunking::default(), control)
+    }
+
+    /// Range generation shared by [`Fib::range_with`] and [`FibEngine`], splitting the range
+    /// according to `chunking` on the current rayon pool.
+    pub(crate) fn range_chunked<B: Backend>(
+        start: u128,
+        end: u128,
+        chunking: Chunking,
+        control: &Control,
+    ) -> Result<Vec<B>, FibError> {
         // Calculate total number of Fibonacci numbers to generate
-        let total_count = (end - start + 1) as usize;
-
-        // Determine optimal chunk size for parallelization based on available CPU threads
-        // This balances parallelism with the overhead of creating too many small chunks
-        let num_threads = current_num_threads();
-        let chunk_size = max(1, total_count / num_threads);
-
-        // Calculate number of chunks with ceiling division to ensure we cover the entire range
-        let num_chunks = total_count.div_ceil(chunk_size);
-
-        // Create chunk boundaries for parallel processing
-        // Each chunk represents a subrange of consecutive Fibonacci numbers
-        let chunks: Vec<_> = (0..num_chunks)
-            .map(|i| {
-                let chunk_start = start + (i as u128) * (chunk_size as u128);
-                let chunk_end = min(chunk_start + (chunk_size as u128) - 1, end);
-                (chunk_start, chunk_end)
-            })
-            .collect();
+        let total_count = error::range_len(start, end)?;
+        control.check()?;
+
+        // Ranges within the table of primitive values need no seeding or parallel chunks
+        if Self::single_u128(end).is_some() {
+            let values = (start..=end)
+                .filter_map(Self::single_u128)
+                .map(B::from_u128)
+                .collect();
+            control.report(1, 1);
+            return Ok(values);
+        }
+
+        let (chunks, seeds) = Self::range_chunks::<B>(start, total_count, chunking, control)?;
+
+        let total_chunks = chunks.len() as u64;
+        let completed_chunks = AtomicU64::new(0);
+        let interruptible = control.is_interruptible();
 
         // Process each chunk in parallel using Rayon's parallel iterator
-        // Each thread calculates a portion of the Fibonacci sequence independently
+        // Each thread extends the seeds of a chunk independently
         chunks
-            .par_iter()
-            .flat_map_iter(|&(chunk_start, chunk_end)| {
-                let chunk_size = (chunk_end - chunk_start + 1) as usize;
-
-                // Use the fast doubling algorithm to efficiently find the starting values
-                // for this chunk. This is a key optimization for chunk initialization.
-                let (mut a, mut b) = Self::fib_fast_doubling_helper(chunk_start);
+            .into_par_iter()
+            .zip(seeds)
+            .flat_map_iter(|(chunk_size, (mut a, mut b))| {
                 let mut remaining = chunk_size;
+                let completed_chunks = &completed_chunks;
 
                 // Compute the chunk iteratively using the recurrence relation:
                 // F(n+2) = F(n+1) + F(n)
@@ -262,22 +590,152 @@ impl Fib {
                     if remaining == 0 {
                         return None;
                     }
-                    let next = &a + &b;
+                    if interruptible
+                        && remaining.is_multiple_of(CONTROL_CHECK_INTERVAL)
+                        && let Err(error) = control.check()
+                    {
+                        remaining = 0;
+                        return Some(Err(error));
+                    }
+                    let next = a.add(&b);
                     let out = take(&mut a);
                     a = replace(&mut b, next);
                     remaining -= 1;
-                    Some(out)
+                    if remaining == 0 {
+                        let completed = completed_chunks.fetch_add(1, Ordering::Relaxed) + 1;
+                        control.report(completed, total_chunks);
+                    }
+                    Some(Ok(out))
                 })
             })
             .collect()
     }
+
+    /// Split a range of `total_count` values from index `start` according to `chunking`, and
+    /// seed every chunk with the pair of values at its first index.
+    ///
+    /// Returns the number of values in each chunk along with its seed.
+    pub(crate) fn range_chunks<B: Backend>(
+        start: u128,
+        total_count: usize,
+        chunking: Chunking,
+        control: &Control,
+    ) -> Result<(Vec<usize>, Vec<FibPair<B>>), FibError> {
+        // Split the range according to the chunking policy
+        // By default chunks hold about the same number of bits, several per thread of the
+        // current pool, so threads that finish early steal the remaining chunks
+        let bounds = chunking.bounds(start, total_count);
+        let lens: Vec<usize> = bounds.windows(2).map(|w| w[1] - w[0]).collect();
+
+        let chunk_starts: Vec<u128> = bounds[..lens.len()]
+            .iter()
+            .map(|&offset| start + offset as u128)
+            .collect();
+        let seeds = Self::chunk_seeds(&chunk_starts, None, control)?;
+        Ok((lens, seeds))
+    }
+
+    /// Seed chunks beginning at the ascending indices `chunk_starts` with (F(m), F(m+1)) for each
+    /// start m, optionally stepping from a known pair at a lower index.
+    pub(crate) fn chunk_seeds<B: Backend>(
+        chunk_starts: &[u128],
+        previous: Option<(u128, &FibPair<B>)>,
+        control: &Control,
+    ) -> Result<Vec<FibPair<B>>, FibError> {
+        // A chunk close enough to its predecessor is seeded from it with the addition formula,
+        // and any other chunk starts a new chain from fast doubling
+        let mut previous_start = previous.map(|(index, _)| index);
+        let near: Vec<bool> = chunk_starts
+            .iter()
+            .map(|&chunk_start| {
+                let near = previous_start.is_some_and(|previous_start| {
+                    (chunk_start - previous_start).saturating_mul(NEIGHBOUR_SEED_RATIO)
+                        <= chunk_start
+                });
+                previous_start = Some(chunk_start);
+                near
+            })
+            .collect();
+        let heads: Vec<usize> = (0..chunk_starts.len())
+            .filter(|&i| i == 0 || !near[i])
+            .collect();
+
+        // The chains are independent, so their heads are doubled in parallel and only the
+        // cheap steps within a chain run one after another
+        let chains = heads
+            .par_iter()
+            .enumerate()
+            .map(|(chain, &head)| {
+                let tail = heads.get(chain + 1).copied().unwrap_or(chunk_starts.len());
+                let mut seeds: Vec<FibPair<B>> = Vec::with_capacity(tail - head);
+                let first = match previous {
+                    Some((index, pair)) if near[head] => {
+                        control.check()?;
+                        Self::fib_advance(pair, chunk_starts[head] - index)
+                    }
+                    _ => {
+                        Self::fib_fast_doubling_steps(chunk_starts[head], &mut || control.check())?
+                    }
+                };
+                seeds.push(first);
+                for i in head + 1..tail {
+                    control.check()?;
+                    let seed = Self::fib_advance(
+                        &seeds[i - head - 1],
+                        chunk_starts[i] - chunk_starts[i - 1],
+                    );
+                    seeds.push(seed);
+                }
+                Ok(seeds)
+            })
+            .collect::<Result<Vec<_>, FibError>>()?;
+        Ok(chains.into_iter().flatten().collect())
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use num_traits::{One, Zero};
     use std::str::FromStr;
 
+    #[test]
+    fn single_matches_pair_helper() {
+        for n in (0..1_100).chain([u128::from(u32::MAX) >> 17, 123_456]) {
+            let (fk, fk1) = Fib::fib_fast_doubling_helper(n);
+            assert_eq!(Fib::single(n), fk, "F({n})");
+            assert_eq!(Fib::single(n + 1), fk1, "F({})", n + 1);
+        }
+    }
+
+    #[test]
+    fn advance_matches_doubling() {
+        for (s, d) in [(0, 1), (1, 1), (5, 3), (300, 1), (300, 200), (12_345, 678)] {
+            let pair = Fib::fib_fast_doubling_helper(s);
+            assert_eq!(
+                Fib::fib_advance(&pair, d),
+                Fib::fib_fast_doubling_helper(s + d),
+                "F({s} + {d})"
+            );
+        }
+    }
+
+    #[test]
+    fn chunk_seeds_match_doubling() {
+        // Starts near zero each begin a chain, while later ones step from their neighbour
+        let starts = [0, 1, 3, 10, 40, 41, 45, 50, 1_000, 1_010, 1_020, 5_000];
+        let expected: Vec<FibPair<BigUint>> = starts
+            .iter()
+            .map(|&m| Fib::fib_fast_doubling_helper(m))
+            .collect();
+        let control = Control::new();
+        assert_eq!(Fib::chunk_seeds(&starts, None, &control).unwrap(), expected);
+
+        let known = Fib::fib_fast_doubling_helper(900);
+        let seeds = Fib::chunk_seeds(&starts[8..], Some((900, &known)), &control).unwrap();
+        assert_eq!(seeds, expected[8..]);
+    }
+
     #[test]
     fn correct_fib_formula() {
         // The formula is respected
@@ -0,0 +1,72 @@
+//! Word-sized modular arithmetic on Fibonacci numbers.
+//!
+//! Residues F(n) mod m are computed with the same fast doubling identities as [`Fib::single`],
+//! but every intermediate value stays below m, so huge indices cost only O(log n) word operations.
+//!
+//! [`Fib::single`]: crate::Fib::single
+
+/// Compute `a * b mod m`.
+#[allow(clippy::cast_possible_truncation)] // The result is reduced modulo a u64
+pub(crate) fn mul_mod(a: u64, b: u64, m: u64) -> u64 {
+    (u128::from(a) * u128::from(b) % u128::from(m)) as u64
+}
+
+/// Compute `base^exp mod m` by square-and-multiply.
+pub(crate) fn pow_mod(base: u64, mut exp: u128, m: u64) -> u64 {
+    let mut base = base % m;
+    let mut result = 1 % m;
+    while exp > 0 {
+        if exp & 1 == 1 {
+            result = mul_mod(result, base, m);
+        }
+        base = mul_mod(base, base, m);
+        exp >>= 1;
+    }
+    result
+}
+
+/// Compute F(n) mod m for a modulus below 2^62.
+#[allow(clippy::similar_names)] // Mathematical notation: F(k), F(k+1), F(2k), F(2k+1)
+pub(crate) fn fib_mod(n: u128, m: u64) -> u64 {
+    debug_assert!(m > 0 && m < 1 << 62);
+    // (F(k), F(k+1)) starting from k = 0, doubling once per bit of n
+    let (mut fk, mut fk1) = (0, 1 % m);
+    for bit in (0..u128::BITS - n.leading_zeros()).rev() {
+        // F(2k) = F(k) * (2*F(k+1) - F(k)) and F(2k+1) = F(k)^2 + F(k+1)^2
+        let f2k = mul_mod(fk, (2 * fk1 + m - fk) % m, m);
+        let f2k1 = (mul_mod(fk, fk, m) + mul_mod(fk1, fk1, m)) % m;
+        if n >> bit & 1 == 1 {
+            (fk, fk1) = (f2k1, (f2k + f2k1) % m);
+        } else {
+            (fk, fk1) = (f2k, f2k1);
+        }
+    }
+    fk
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Fib;
+    use num_bigint::BigUint;
+
+    #[test]
+    fn residues_match_exact_values() {
+        let moduli = [1u64, 2, 10, 1_000_000_007, (1 << 61) - 1, (1 << 62) - 57];
+        for n in (0..500).chain([1_000, 4_321, 10_000]) {
+            let exact = Fib::single(n);
+            for m in moduli {
+                assert_eq!(BigUint::from(fib_mod(n, m)), &exact % m, "F({n}) mod {m}");
+            }
+        }
+    }
+
+    #[test]
+    fn powers() {
+        assert_eq!(pow_mod(2, 10, 1_000), 24);
+        assert_eq!(pow_mod(10, 0, 7), 1);
+        assert_eq!(pow_mod(3, 1 << 100, 1), 0);
+        // Fermat's little theorem
+        assert_eq!(pow_mod(12_345, (1 << 61) - 2, (1 << 61) - 1), 1);
+    }
+}
@@ -0,0 +1,232 @@
+//! Parallel multiplication of large integers.
+//!
+//! `num-bigint` multiplies on a single thread. Above a size threshold, a product is split
+//! Karatsuba-style into three half-size products that run concurrently on the rayon pool.
+//! Splitting stops once there are enough sub-products to occupy every thread, since each level
+//! adds temporaries and further splits would only raise peak memory. Below the threshold, and
+//! on a single thread, multiplication is left to `num-bigint` unchanged.
+//!
+//! Products that are not split further switch from `num-bigint` to number-theoretic transforms
+//! (see [`crate::ntt`]) once both operands are large enough for O(n log n) multiplication to win.
+
+use num_bigint::BigUint;
+use rayon::{current_num_threads, join};
+
+use crate::ntt;
+
+/// Operands with fewer bits than this are multiplied sequentially.
+const PARALLEL_MUL_BITS: u64 = 1 << 17;
+
+/// Operands with at least this many bits are multiplied with number-theoretic transforms.
+const NTT_MUL_BITS: u64 = 1 << 22;
+
+/// Most levels of splitting, giving up to 27 concurrent sub-products.
+///
+/// Every level keeps its split operands and partial products alive, so the cap bounds the
+/// extra memory of a parallel multiplication.
+const MAX_SPLIT_DEPTH: u32 = 3;
+
+/// Compute `a * b`, splitting large products into parallel sub-products.
+pub(crate) fn mul(a: &BigUint, b: &BigUint) -> BigUint {
+    mul_split(a, b, split_depth())
+}
+
+/// Compute `a^2` and `b^2` concurrently, each split into parallel sub-products.
+pub(crate) fn square_pair(a: &BigUint, b: &BigUint) -> (BigUint, BigUint) {
+    if a.bits().max(b.bits()) < PARALLEL_MUL_BITS || current_num_threads() == 1 {
+        return (square_leaf(a), square_leaf(b));
+    }
+    // The pair already provides two tasks, so each square needs one level less
+    let depth = split_depth().saturating_sub(1);
+    join(|| square_split(a, depth), || square_split(b, depth))
+}
+
+/// Number of Karatsuba levels needed for 3^depth sub-products to cover every thread, capped
+/// at [`MAX_SPLIT_DEPTH`].
+pub(crate) fn split_depth() -> u32 {
+    let threads = current_num_threads();
+    let mut depth = 0;
+    while depth < MAX_SPLIT_DEPTH && 3usize.pow(depth) < threads {
+        depth += 1;
+    }
+    depth
+}
+
+/// Compute `a * b` with up to `depth` levels of parallel Karatsuba splitting.
+fn mul_split(a: &BigUint, b: &BigUint, depth: u32) -> BigUint {
+    if depth == 0 || a.bits().min(b.bits()) < PARALLEL_MUL_BITS {
+        return mul_leaf(a, b);
+    }
+
+    // Split both operands at half the larger size, rounded to whole limbs
+    let split = (a.bits().max(b.bits()) / 2).next_multiple_of(64);
+    if a.bits() <= split {
+        return mul_unbalanced(a, b, split, depth);
+    }
+    if b.bits() <= split {
+        return mul_unbalanced(b, a, split, depth);
+    }
+
+    // Karatsuba: a * b = p2 * 2^(2s) + (pm - p2 - p0) * 2^s + p0
+    let (p0, p2, pm) = {
+        let (a0, a1) = split_at(a, split);
+        let (b0, b1) = split_at(b, split);
+        let ((p0, p2), pm) = join(
+            || {
+                join(
+                    || mul_split(&a0, &b0, depth - 1),
+                    || mul_split(&a1, &b1, depth - 1),
+                )
+            },
+            || mul_split(&(&a0 + &a1), &(&b0 + &b1), depth - 1),
+        );
+        (p0, p2, pm)
+    };
+    combine(p0, p2, pm, split)
+}
+
+/// Compute `a^2` with up to `depth` levels of parallel Karatsuba splitting.
+fn square_split(a: &BigUint, depth: u32) -> BigUint {
+    if depth == 0 || a.bits() < PARALLEL_MUL_BITS {
+        return square_leaf(a);
+    }
+
+    // Karatsuba squaring: a^2 = a1^2 * 2^(2s) + ((a0 + a1)^2 - a1^2 - a0^2) * 2^s + a0^2
+    let split = (a.bits() / 2).next_multiple_of(64);
+    let (p0, p2, pm) = {
+        let (a0, a1) = split_at(a, split);
+        let ((p0, p2), pm) = join(
+            || {
+                join(
+                    || square_split(&a0, depth - 1),
+                    || square_split(&a1, depth - 1),
+                )
+            },
+            || square_split(&(&a0 + &a1), depth - 1),
+        );
+        (p0, p2, pm)
+    };
+    combine(p0, p2, pm, split)
+}
+
+/// Compute `a * b` without splitting, with number-theoretic transforms for huge operands.
+fn mul_leaf(a: &BigUint, b: &BigUint) -> BigUint {
+    if a.bits().min(b.bits()) >= NTT_MUL_BITS {
+        ntt::mul(a, b)
+    } else {
+        a * b
+    }
+}
+
+/// Compute `a^2` without splitting, with number-theoretic transforms for huge operands.
+fn square_leaf(a: &BigUint) -> BigUint {
+    if a.bits() >= NTT_MUL_BITS {
+        ntt::square(a)
+    } else {
+        a * a
+    }
+}
+
+/// Assemble p2 * 2^(2s) + (pm - p2 - p0) * 2^s + p0 from the three Karatsuba products.
+fn combine(p0: BigUint, p2: BigUint, mut pm: BigUint, split: u64) -> BigUint {
+    pm -= &p0;
+    pm -= &p2;
+    let mut product = p2 << (2 * split);
+    product += pm << split;
+    product += p0;
+    product
+}
+
+/// Compute `short * long` where only `long` has bits above `split`.
+fn mul_unbalanced(short: &BigUint, long: &BigUint, split: u64, depth: u32) -> BigUint {
+    let (low, high) = split_at(long, split);
+    let (low, high) = join(
+        || mul_split(short, &low, depth - 1),
+        || mul_split(short, &high, depth - 1),
+    );
+    let mut product = high << split;
+    product += low;
+    product
+}
+
+/// Split `x` into its low `bits` bits and the remaining high part.
+fn split_at(x: &BigUint, bits: u64) -> (BigUint, BigUint) {
+    debug_assert!(bits.is_multiple_of(64));
+    let low_digits = usize::try_from(bits / 32).unwrap_or(usize::MAX);
+    let low = BigUint::new(x.iter_u32_digits().take(low_digits).collect());
+    (low, x >> bits)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use num_traits::One;
+    use rayon::ThreadPoolBuilder;
+
+    /// Run `op` on a multi-threaded pool, so the parallel path is taken even on one core.
+    fn on_pool<T: Send>(op: impl FnOnce() -> T + Send) -> T {
+        ThreadPoolBuilder::new()
+            .num_threads(4)
+            .build()
+            .unwrap()
+            .install(op)
+    }
+
+    fn pseudo_random(bits: u64, seed: u32) -> BigUint {
+        let mut state = seed;
+        let digits = (0..bits.div_ceil(32))
+            .map(|_| {
+                state = state.wrapping_mul(1_664_525).wrapping_add(1_013_904_223);
+                state
+            })
+            .collect();
+        BigUint::new(digits) >> (bits.div_ceil(32) * 32 - bits)
+    }
+
+    #[test]
+    fn products_match_sequential() {
+        let sizes = [
+            1,
+            64,
+            PARALLEL_MUL_BITS,
+            2 * PARALLEL_MUL_BITS + 17,
+            5 * PARALLEL_MUL_BITS,
+        ];
+        for (i, &x) in (1..).zip(&sizes) {
+            for (j, &y) in (100..).zip(&sizes) {
+                let a = pseudo_random(x, i);
+                let b = pseudo_random(y, j);
+                assert_eq!(on_pool(|| mul(&a, &b)), &a * &b, "{x} x {y} bits");
+            }
+        }
+    }
+
+    #[test]
+    fn edge_values() {
+        let big = (BigUint::one() << (4 * PARALLEL_MUL_BITS)) - 1u32;
+        assert_eq!(on_pool(|| mul(&big, &big)), &big * &big);
+        let sparse = (BigUint::one() << (4 * PARALLEL_MUL_BITS)) + 1u32;
+        assert_eq!(on_pool(|| mul(&sparse, &big)), &sparse * &big);
+        assert_eq!(
+            on_pool(|| square_pair(&big, &sparse)),
+            (&big * &big, &sparse * &sparse)
+        );
+    }
+
+    #[test]
+    fn ntt_leaves() {
+        let a = pseudo_random(NTT_MUL_BITS + 1_000, 7);
+        let b = pseudo_random(NTT_MUL_BITS + 77, 8);
+        assert_eq!(mul_leaf(&a, &b), &a * &b);
+        assert_eq!(square_leaf(&a), &a * &a);
+    }
+
+    #[test]
+    fn parallel_single() {
+        let n = 1_000_000;
+        assert_eq!(
+            on_pool(|| crate::Fib::single(n)),
+            crate::Algorithm::Lucas.single(n)
+        );
+    }
+}
@@ -0,0 +1,370 @@
+//! Number-theoretic transform multiplication of very large integers.
+//!
+//! `num-bigint` multiplies with at best Toom-3, which takes O(n^1.465) time. Above a few million
+//! bits this module multiplies in O(n log n) instead: the 64-bit limbs of each operand are
+//! transformed modulo three primes c * 2^50 + 1 between 2^61 and 2^62, multiplied pointwise and
+//! transformed back, and the exact coefficients of the limb convolution are recovered with the
+//! Chinese remainder theorem before carrying.
+//!
+//! Every coefficient is a sum of at most N products of two limbs, below N * 2^128, which stays
+//! under the product of the three primes (above 2^183) for any transform length N up to 2^50.
+//! The primes are handled one after another, so only one set of transforms is alive at a time.
+
+use num_bigint::BigUint;
+
+/// The NTT primes c * 2^50 + 1, each with a primitive root.
+pub(crate) const PRIMES: [Prime; 3] = [
+    Prime::new(0x3fdc_0000_0000_0001, 3),
+    Prime::new(0x3f18_0000_0000_0001, 10),
+    Prime::new(0x3ec4_0000_0000_0001, 37),
+];
+
+/// Largest supported transform length, log2, set by the power of two dividing every p - 1.
+const MAX_LOG_LEN: u32 = 50;
+
+/// Compute `a * b` with number-theoretic transforms.
+pub(crate) fn mul(a: &BigUint, b: &BigUint) -> BigUint {
+    convolve(a, Some(b))
+}
+
+/// Compute `a^2` with number-theoretic transforms, transforming `a` only once.
+pub(crate) fn square(a: &BigUint) -> BigUint {
+    convolve(a, None)
+}
+
+/// Multiply `a` by `b`, or square `a` if `b` is `None`.
+fn convolve(a: &BigUint, b: Option<&BigUint>) -> BigUint {
+    let a_len = limb_len(a);
+    let b_len = b.map_or(a_len, limb_len);
+    if a_len == 0 || b_len == 0 {
+        return BigUint::ZERO;
+    }
+
+    // The convolution of the limbs has a_len + b_len - 1 coefficients
+    let len = a_len + b_len - 1;
+    let log_len = len.next_power_of_two().trailing_zeros();
+    assert!(log_len <= MAX_LOG_LEN, "operands too large for NTT");
+    let residues = PRIMES.map(|prime| prime.convolve(a, b, len, log_len));
+    crt(&residues)
+}
+
+/// Number of 64-bit limbs of `x`.
+fn limb_len(x: &BigUint) -> usize {
+    usize::try_from(x.bits().div_ceil(64)).unwrap_or(usize::MAX)
+}
+
+/// Recover the limbs of the product from the convolution residues modulo each prime and carry
+/// them into a `BigUint`.
+#[allow(clippy::cast_possible_truncation)] // Splitting wide values into their 64-bit words
+fn crt(residues: &[Vec<u64>; 3]) -> BigUint {
+    let garner = Garner::new();
+
+    // The carried sum low + high * 2^128, from which one limb is emitted per coefficient
+    let (mut low, mut high) = (0u128, 0u64);
+    let mut digits = Vec::with_capacity(2 * residues[0].len() + 6);
+    for ((&r0, &r1), &r2) in residues[0].iter().zip(&residues[1]).zip(&residues[2]) {
+        let [w0, w1, w2] = garner.words(r0, r1, r2);
+        let carry;
+        (low, carry) = low.overflowing_add(u128::from(w0) | (u128::from(w1) << 64));
+        high += w2 + u64::from(carry);
+
+        digits.extend([low as u32, (low >> 32) as u32]);
+        low = (low >> 64) | (u128::from(high) << 64);
+        high = 0;
+    }
+    digits.extend([
+        low as u32,
+        (low >> 32) as u32,
+        (low >> 64) as u32,
+        (low >> 96) as u32,
+    ]);
+    BigUint::new(digits)
+}
+
+/// Chinese remaindering of residues modulo the three [`PRIMES`] by Garner's algorithm.
+pub(crate) struct Garner {
+    /// p0^-1 mod p1, in Montgomery form
+    inv_p0: u64,
+    /// p0 mod p2, in Montgomery form
+    p0_mod_p2: u64,
+    /// (p0 * p1)^-1 mod p2, in Montgomery form
+    inv_p0p1: u64,
+    /// The low and high words of p0 * p1
+    p0p1: (u64, u64),
+}
+
+impl Garner {
+    #[allow(clippy::cast_possible_truncation)] // Splitting p0 * p1 into its 64-bit words
+    pub(crate) fn new() -> Self {
+        let [p0, p1, p2] = PRIMES;
+        // Constants in Montgomery form, so that multiplying by them leaves plain values plain
+        let p0_mod_p2 = p2.to_montgomery(p2.reduce(p0.p));
+        let p0p1 = u128::from(p0.p) * u128::from(p1.p);
+        Self {
+            inv_p0: p1.to_montgomery(p1.inverse(p1.reduce(p0.p))),
+            p0_mod_p2,
+            inv_p0p1: p2.to_montgomery(p2.inverse(p2.mul(p0_mod_p2, p2.reduce(p1.p)))),
+            p0p1: (p0p1 as u64, (p0p1 >> 64) as u64),
+        }
+    }
+
+    /// The value below p0 * p1 * p2 with residues `r0`, `r1` and `r2`, as three 64-bit words
+    /// from the least significant.
+    #[allow(clippy::cast_possible_truncation)] // Splitting wide values into their 64-bit words
+    pub(crate) fn words(&self, r0: u64, r1: u64, r2: u64) -> [u64; 3] {
+        let [p0, p1, p2] = PRIMES;
+        // x = r0 + v1 * p0 + v2 * p0 * p1 with v1 < p1 and v2 < p2
+        let v1 = p1.mul(p1.sub(r1, p1.reduce(r0)), self.inv_p0);
+        let v2 = p2.sub(
+            p2.sub(r2, p2.reduce(r0)),
+            p2.mul(p2.reduce(v1), self.p0_mod_p2),
+        );
+        let v2 = p2.mul(v2, self.inv_p0p1);
+
+        let (low, carried) = (u128::from(r0) + u128::from(v1) * u128::from(p0.p))
+            .overflowing_add(u128::from(v2) * u128::from(self.p0p1.0));
+        let top = u128::from(v2) * u128::from(self.p0p1.1);
+        let (low, shifted) = low.overflowing_add(top << 64);
+        let high = (top >> 64) as u64 + u64::from(carried) + u64::from(shifted);
+        [low as u64, (low >> 64) as u64, high]
+    }
+}
+
+/// An NTT prime with its Montgomery arithmetic constants.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct Prime {
+    /// The prime modulus
+    pub(crate) p: u64,
+    /// A primitive root modulo p
+    root: u64,
+    /// p^-1 mod 2^64
+    p_inv: u64,
+    /// 2^128 mod p, which converts values to Montgomery form
+    r2: u64,
+}
+
+impl Prime {
+    #[allow(clippy::cast_possible_truncation)] // The remainders are below p
+    const fn new(p: u64, root: u64) -> Self {
+        // Each Newton step doubles the number of correct low bits of p^-1, starting from 1
+        let mut p_inv: u64 = 1;
+        let mut i = 0;
+        while i < 6 {
+            p_inv = p_inv.wrapping_mul(2u64.wrapping_sub(p.wrapping_mul(p_inv)));
+            i += 1;
+        }
+        let r = (1u128 << 64) % p as u128;
+        Self {
+            p,
+            root,
+            p_inv,
+            r2: (r * r % p as u128) as u64,
+        }
+    }
+
+    /// Reduce a value below 2p, such as a residue modulo another of the primes.
+    pub(crate) fn reduce(self, a: u64) -> u64 {
+        if a >= self.p { a - self.p } else { a }
+    }
+
+    pub(crate) fn add(self, a: u64, b: u64) -> u64 {
+        self.reduce(a + b)
+    }
+
+    pub(crate) fn sub(self, a: u64, b: u64) -> u64 {
+        if a >= b { a - b } else { a + self.p - b }
+    }
+
+    /// Montgomery multiplication, a * b / 2^64 mod p.
+    #[allow(clippy::cast_possible_truncation)] // Splitting products into their 64-bit words
+    pub(crate) fn mul(self, a: u64, b: u64) -> u64 {
+        let t = u128::from(a) * u128::from(b);
+        // t - m * p is divisible by 2^64, leaving the difference of the high words
+        let m = (t as u64).wrapping_mul(self.p_inv);
+        let mp = u128::from(m) * u128::from(self.p);
+        self.sub((t >> 64) as u64, (mp >> 64) as u64)
+    }
+
+    pub(crate) fn to_montgomery(self, a: u64) -> u64 {
+        self.mul(a, self.r2)
+    }
+
+    /// Raise a value in Montgomery form to the power `exp`.
+    pub(crate) fn pow(self, mut base: u64, mut exp: u64) -> u64 {
+        let mut result = self.to_montgomery(1);
+        while exp > 0 {
+            if exp & 1 == 1 {
+                result = self.mul(result, base);
+            }
+            base = self.mul(base, base);
+            exp >>= 1;
+        }
+        result
+    }
+
+    /// Inverse of a nonzero value by Fermat's little theorem, in the same form as its input.
+    fn inverse(self, a: u64) -> u64 {
+        // pow works in Montgomery form, so convert the plain input in and the result back out
+        self.mul(self.pow(self.to_montgomery(a), self.p - 2), 1)
+    }
+
+    /// Residues of the first `len` coefficients of the limb convolution `a * b`, or `a * a`.
+    fn convolve(self, a: &BigUint, b: Option<&BigUint>, len: usize, log_len: u32) -> Vec<u64> {
+        let roots = self.roots(log_len);
+        let mut fa = self.load(a, 1 << log_len);
+        self.forward(&mut fa, &roots);
+
+        // The pointwise product is a * b / 2^64; multiplying by 2^128 / N in Montgomery form
+        // restores a * b and cancels the factor N gained by the inverse transform
+        let n_inv = self.p - ((self.p - 1) >> log_len);
+        let scale = self.to_montgomery(self.to_montgomery(n_inv));
+        if let Some(b) = b {
+            let mut fb = self.load(b, 1 << log_len);
+            self.forward(&mut fb, &roots);
+            for (x, &y) in fa.iter_mut().zip(&fb) {
+                *x = self.mul(self.mul(*x, y), scale);
+            }
+        } else {
+            for x in &mut fa {
+                *x = self.mul(self.mul(*x, *x), scale);
+            }
+        }
+
+        self.inverse_transform(&mut fa, &roots);
+        fa.truncate(len);
+        fa.shrink_to_fit();
+        fa
+    }
+
+    /// A primitive Nth root of unity for N = 2^`log_len`, in Montgomery form.
+    pub(crate) fn root_of_unity(self, log_len: u32) -> u64 {
+        self.pow(self.to_montgomery(self.root), (self.p - 1) >> log_len)
+    }
+
+    /// Powers w^j for j < N / 2 of the primitive Nth root of unity w, in Montgomery form.
+    pub(crate) fn roots(self, log_len: u32) -> Vec<u64> {
+        let half = (1usize << log_len) / 2;
+        let w = self.root_of_unity(log_len);
+        let mut roots = Vec::with_capacity(half);
+        let mut power = self.to_montgomery(1);
+        for _ in 0..half {
+            roots.push(power);
+            power = self.mul(power, w);
+        }
+        roots
+    }
+
+    /// The limbs of `x` reduced modulo p, zero-padded to `size`.
+    fn load(self, x: &BigUint, size: usize) -> Vec<u64> {
+        let mut values = vec![0; size];
+        for (value, limb) in values.iter_mut().zip(x.iter_u64_digits()) {
+            *value = limb % self.p;
+        }
+        values
+    }
+
+    /// Forward transform by decimation in frequency, from natural to bit-reversed order.
+    pub(crate) fn forward(self, values: &mut [u64], roots: &[u64]) {
+        let size = values.len();
+        let mut half = size / 2;
+        while half >= 1 {
+            let stride = size / 2 / half;
+            for block in values.chunks_exact_mut(2 * half) {
+                let (low, high) = block.split_at_mut(half);
+                for (j, (x, y)) in low.iter_mut().zip(high).enumerate() {
+                    let (u, v) = (*x, *y);
+                    *x = self.add(u, v);
+                    *y = self.mul(self.sub(u, v), roots[j * stride]);
+                }
+            }
+            half /= 2;
+        }
+    }
+
+    /// Inverse transform without scaling by decimation in time, from bit-reversed to natural
+    /// order.
+    ///
+    /// The inverse roots are w^-j = -w^(N/2 - j), so the forward table serves both directions.
+    pub(crate) fn inverse_transform(self, values: &mut [u64], roots: &[u64]) {
+        let size = values.len();
+        let mut half = 1;
+        while half < size {
+            let stride = size / 2 / half;
+            for block in values.chunks_exact_mut(2 * half) {
+                let (low, high) = block.split_at_mut(half);
+                let (u, v) = (low[0], high[0]);
+                low[0] = self.add(u, v);
+                high[0] = self.sub(u, v);
+                for (j, (x, y)) in low.iter_mut().zip(high).enumerate().skip(1) {
+                    let (u, v) = (*x, self.mul(*y, roots[size / 2 - j * stride]));
+                    *x = self.sub(u, v);
+                    *y = self.add(u, v);
+                }
+            }
+            half *= 2;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use num_traits::One;
+
+    fn pseudo_random(limbs: usize, seed: u64) -> BigUint {
+        let mut state = seed;
+        let digits: Vec<u32> = (0..2 * limbs)
+            .map(|_| {
+                state = state
+                    .wrapping_mul(6_364_136_223_846_793_005)
+                    .wrapping_add(1_442_695_040_888_963_407);
+                u32::try_from(state >> 32).unwrap()
+            })
+            .collect();
+        BigUint::new(digits)
+    }
+
+    #[test]
+    fn montgomery_arithmetic() {
+        for prime in PRIMES {
+            assert_eq!(prime.p & ((1 << MAX_LOG_LEN) - 1), 1);
+            assert_eq!(prime.p.wrapping_mul(prime.p_inv), 1);
+            let x = prime.to_montgomery(123_456_789);
+            assert_eq!(prime.mul(x, 1), 123_456_789);
+            let inverse = prime.to_montgomery(prime.inverse(987_654_321));
+            assert_eq!(prime.mul(inverse, 987_654_321), 1);
+            // The root is a quadratic non-residue, so its order has the full power of two
+            let root = prime.to_montgomery(prime.root);
+            let one = prime.to_montgomery(1);
+            assert_eq!(prime.pow(root, prime.p - 1), one);
+            assert_ne!(prime.pow(root, (prime.p - 1) / 2), one);
+        }
+    }
+
+    #[test]
+    fn products_match_num_bigint() {
+        let sizes = [1, 2, 3, 17, 64, 1_000, 4_097];
+        for (i, &x) in (1..).zip(&sizes) {
+            let a = pseudo_random(x, i);
+            assert_eq!(square(&a), &a * &a, "{x} limbs squared");
+            for (j, &y) in (100..).zip(&sizes) {
+                let b = pseudo_random(y, j);
+                assert_eq!(mul(&a, &b), &a * &b, "{x} x {y} limbs");
+            }
+        }
+    }
+
+    #[test]
+    fn edge_values() {
+        let zero = BigUint::ZERO;
+        let one = BigUint::one();
+        // All-ones limbs maximize every coefficient of the convolution
+        let max = (BigUint::one() << (64 * 3_000)) - 1u32;
+        assert_eq!(mul(&zero, &max), zero);
+        assert_eq!(mul(&one, &max), max);
+        assert_eq!(square(&max), &max * &max);
+        assert_eq!(square(&one), one);
+        let sparse = (BigUint::one() << (64 * 2_000 + 5)) + 1u32;
+        assert_eq!(mul(&sparse, &max), &sparse * &max);
+    }
+}
@@ -0,0 +1,296 @@
+//! Rayon parallel iteration over Fibonacci ranges.
+//!
+//! [`FibParRange`] hands out index ranges to rayon, which splits them between threads as it
+//! would a slice. Each piece seeds itself lazily with fast doubling at whichever end is consumed
+//! first and then steps through its values with additions, or subtractions when iterated from
+//! the back, so no values are computed before a consumer asks for them.
+
+use num_bigint::BigUint;
+use rayon::iter::{
+    IndexedParallelIterator, ParallelIterator,
+    plumbing::{Consumer, Producer, ProducerCallback, UnindexedConsumer, bridge},
+};
+
+use crate::{Fib, FibError, FibPair, error};
+
+/// Parallel iterator over `(n, F(n))` for the indices of an inclusive range, created by
+/// [`Fib::par_range`].
+///
+/// # Examples
+///
+/// ```
+/// use fib_rs::Fib;
+/// use rayon::prelude::*;
+///
+/// let sum: num_bigint::BigUint = Fib::par_range(0, 10).map(|(_, value)| value).sum();
+/// assert_eq!(sum, Fib::single(12) - 1u32);
+/// ```
+#[derive(Debug, Clone)]
+pub struct FibParRange {
+    start: u128,
+    len: usize,
+}
+
+impl Fib {
+    /// Iterate in parallel over `(n, F(n))` for indices in the given inclusive range.
+    ///
+    /// Values are produced on demand by rayon's worker threads and never collected, so a
+    /// `filter` or `reduce` over millions of values keeps only the values in flight in memory.
+    /// An inverted range yields nothing.
+    ///
+    /// # Arguments
+    ///
+    /// * `start` - The starting index of the range
+    /// * `end` - The ending index of the range (inclusive)
+    ///
+    /// # Returns
+    ///
+    /// * An indexed parallel iterator over the pairs `(n, F(n))` in ascending order of `n`
+    ///
+    /// # Panics
+    ///
+    /// * If the number of values does not fit in `usize`
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use fib_rs::Fib;
+    /// use rayon::prelude::*;
+    ///
+    /// // F(n) is divisible by 7 exactly when n is divisible by 8
+    /// let count = Fib::par_range(1, 1_000)
+    ///     .filter(|(_, value)| (value % 7u32) == 0u32.into())
+    ///     .count();
+    /// assert_eq!(count, 125);
+    /// ```
+    #[must_use]
+    pub fn par_range(start: u128, end: u128) -> FibParRange {
+        match Self::try_par_range(start, end) {
+            Ok(iter) => iter,
+            Err(FibError::InvertedRange { .. }) => FibParRange { start, len: 0 },
+            Err(error) => panic!("{error}"),
+        }
+    }
+
+    /// Iterate in parallel over `(n, F(n))` for indices in the given inclusive range, reporting
+    /// invalid ranges as errors.
+    ///
+    /// # Arguments
+    ///
+    /// * `start` - The starting index of the range
+    /// * `end` - The ending index of the range (inclusive)
+    ///
+    /// # Returns
+    ///
+    /// * An indexed parallel iterator over the pairs `(n, F(n))` in ascending order of `n`
+    ///
+    /// # Errors
+    ///
+    /// * `FibError::InvertedRange` if `end < start`
+    /// * `FibError::CountOverflow` if the number of values does not fit in `usize`
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use fib_rs::{Fib, FibError};
+    /// use rayon::prelude::*;
+    ///
+    /// assert_eq!(Fib::try_par_range(3, 10).unwrap().len(), 8);
+    /// assert!(matches!(
+    ///     Fib::try_par_range(10, 3),
+    ///     Err(FibError::InvertedRange { .. })
+    /// ));
+    /// ```
+    pub fn try_par_range(start: u128, end: u128) -> Result<FibParRange, FibError> {
+        let len = error::range_len(start, end)?;
+        Ok(FibParRange { start, len })
+    }
+}
+
+impl ParallelIterator for FibParRange {
+    type Item = (u128, BigUint);
+
+    fn drive_unindexed<C>(self, consumer: C) -> C::Result
+    where
+        C: UnindexedConsumer<Self::Item>,
+    {
+        bridge(self, consumer)
+    }
+
+    fn opt_len(&self) -> Option<usize> {
+        Some(self.len)
+    }
+}
+
+impl IndexedParallelIterator for FibParRange {
+    fn len(&self) -> usize {
+        self.len
+    }
+
+    fn drive<C: Consumer<Self::Item>>(self, consumer: C) -> C::Result {
+        bridge(self, consumer)
+    }
+
+    fn with_producer<CB: ProducerCallback<Self::Item>>(self, callback: CB) -> CB::Output {
+        callback.callback(RangeProducer {
+            start: self.start,
+            len: self.len,
+        })
+    }
+}
+
+/// A piece of a [`FibParRange`] that rayon can split further or iterate sequentially.
+struct RangeProducer {
+    start: u128,
+    len: usize,
+}
+
+impl Producer for RangeProducer {
+    type Item = (u128, BigUint);
+    type IntoIter = RangeIter;
+
+    fn into_iter(self) -> RangeIter {
+        RangeIter {
+            front: self.start,
+            back: self.start + self.len as u128,
+            front_pair: None,
+            back_pair: None,
+        }
+    }
+
+    fn split_at(self, index: usize) -> (Self, Self) {
+        let left = Self {
+            start: self.start,
+            len: index,
+        };
+        let right = Self {
+            start: self.start + index as u128,
+            len: self.len - index,
+        };
+        (left, right)
+    }
+}
+
+/// Sequential iterator over the indices `front..back`, seeded lazily at each end.
+struct RangeIter {
+    front: u128,
+    back: u128,
+    /// (F(front), F(front+1)), once the front has been seeded.
+    front_pair: Option<FibPair>,
+    /// (F(back-1), F(back)), once the back has been seeded.
+    back_pair: Option<FibPair>,
+}
+
+impl Iterator for RangeIter {
+    type Item = (u128, BigUint);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.front == self.back {
+            return None;
+        }
+        let n = self.front;
+        self.front += 1;
+        let (a, b) = self
+            .front_pair
+            .get_or_insert_with(|| Fib::fib_fast_doubling_helper(n));
+        // The last value needs no successor
+        if self.front == self.back {
+            return Some((n, std::mem::take(a)));
+        }
+        let next = &*a + &*b;
+        let value = std::mem::replace(a, std::mem::replace(b, next));
+        Some((n, value))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.len();
+        (len, Some(len))
+    }
+}
+
+impl DoubleEndedIterator for RangeIter {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.front == self.back {
+            return None;
+        }
+        self.back -= 1;
+        let n = self.back;
+        let (a, b) = self
+            .back_pair
+            .get_or_insert_with(|| Fib::fib_fast_doubling_helper(n));
+        if self.front == self.back {
+            return Some((n, std::mem::take(a)));
+        }
+        // Step down with F(n-1) = F(n+1) - F(n), reusing the buffer of F(n+1)
+        *b -= &*a;
+        std::mem::swap(a, b);
+        Some((n, b.clone()))
+    }
+}
+
+impl ExactSizeIterator for RangeIter {
+    fn len(&self) -> usize {
+        usize::try_from(self.back - self.front).expect("range length fits in usize")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rayon::prelude::*;
+
+    use super::*;
+
+    #[test]
+    fn par_range_matches_range() {
+        let pool = rayon::ThreadPoolBuilder::new()
+            .num_threads(4)
+            .build()
+            .unwrap();
+        pool.install(|| {
+            for (start, end) in [(0, 0), (0, 1), (0, 300), (5, 7), (1_000, 1_500)] {
+                let expected = Fib::range(start, end);
+                let values: Vec<_> = Fib::par_range(start, end).with_max_len(7).collect();
+                assert_eq!(values.len(), expected.len());
+                for ((n, value), expected) in values.into_iter().zip(&expected) {
+                    assert_eq!(&value, expected, "F({n})");
+                }
+                let indices: Vec<u128> = Fib::par_range(start, end).map(|(n, _)| n).collect();
+                assert_eq!(indices, (start..=end).collect::<Vec<_>>());
+            }
+        });
+    }
+
+    #[test]
+    fn reversed_and_mixed_iteration() {
+        let expected = Fib::range(200, 260);
+        let reversed: Vec<BigUint> = Fib::par_range(200, 260)
+            .rev()
+            .with_max_len(5)
+            .map(|(_, value)| value)
+            .collect();
+        assert!(reversed.iter().eq(expected.iter().rev()));
+
+        let mut iter = RangeProducer {
+            start: 200,
+            len: 61,
+        }
+        .into_iter();
+        assert_eq!(iter.len(), 61);
+        assert_eq!(iter.next_back(), Some((260, expected[60].clone())));
+        assert_eq!(iter.next(), Some((200, expected[0].clone())));
+        assert_eq!(iter.next_back(), Some((259, expected[59].clone())));
+        let middle: Vec<BigUint> = iter.by_ref().map(|(_, value)| value).collect();
+        assert_eq!(middle, expected[1..59]);
+        assert_eq!(iter.next_back(), None);
+    }
+
+    #[test]
+    fn invalid_ranges() {
+        assert_eq!(Fib::par_range(10, 3).count(), 0);
+        assert_eq!(Fib::par_range(3, 3).len(), 1);
+        assert!(matches!(
+            Fib::try_par_range(0, u128::MAX),
+            Err(FibError::CountOverflow { .. })
+        ));
+    }
+}
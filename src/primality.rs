@@ -0,0 +1,301 @@
+//! Baillie–PSW probable-prime testing.
+//!
+//! The test combines a strong Fermat test to base 2 with a strong Lucas test using
+//! Selfridge's parameters. The Lucas part is driven by the same doubling identities
+//! that power [`Fib::single`](crate::Fib::single), applied to the generalized Lucas
+//! sequences U(P, Q) and V(P, Q) modulo the candidate:
+//!
+//! - U(2k) = U(k) * V(k)
+//! - V(2k) = V(k)^2 - 2*Q^k
+//! - U(k+1) = (P*U(k) + V(k)) / 2
+//! - V(k+1) = (D*U(k) + P*V(k)) / 2
+//!
+//! With P = 1 and Q = -1 these are exactly the Fibonacci and Lucas numbers.
+//! No composite passing Baillie–PSW is known, and none exist below 2^64.
+
+use num_bigint::BigUint;
+use num_traits::{One, Zero};
+
+/// Small primes used for quick trial division before the probabilistic tests.
+const SMALL_PRIMES: [u32; 25] = [
+    2, 3, 5, 7, 11, 13, 17, 19, 23, 29, 31, 37, 41, 43, 47, 53, 59, 61, 67, 71, 73, 79, 83, 89, 97,
+];
+
+/// Test whether `n` is a probable prime using the Baillie–PSW algorithm.
+///
+/// The result is exact for every `n < 2^64`. For larger inputs a `true` result means
+/// `n` is prime with overwhelming likelihood: no Baillie–PSW pseudoprime has ever been found.
+/// A `false` result is always correct.
+///
+/// # Arguments
+///
+/// * `n` - The candidate to test
+///
+/// # Returns
+///
+/// * `true` if `n` is a (probable) prime, `false` if `n` is composite, 0 or 1
+///
+/// # Examples
+///
+/// ```
+/// use fib_rs::{Fib, is_probable_prime};
+/// use num_bigint::BigUint;
+///
+/// assert!(is_probable_prime(&BigUint::from(97u32)));
+/// assert!(!is_probable_prime(&BigUint::from(561u32))); // Carmichael number
+///
+/// // F(83) is a Fibonacci prime, F(84) is not
+/// assert!(is_probable_prime(&Fib::single(83)));
+/// assert!(!is_probable_prime(&Fib::single(84)));
+/// ```
+#[must_use]
+pub fn is_probable_prime(n: &BigUint) -> bool {
+    // Dispose of tiny inputs and those with small factors
+    for &p in &SMALL_PRIMES {
+        if *n == BigUint::from(p) {
+            return true;
+        }
+        if (n % p).is_zero() {
+            return false;
+        }
+    }
+    if *n < BigUint::from(2u32) {
+        return false;
+    }
+
+    if !is_strong_probable_prime_base_2(n) {
+        return false;
+    }
+
+    // A perfect square would make the search for D below loop forever
+    if is_perfect_square(n) {
+        return false;
+    }
+
+    match selfridge_parameters(n) {
+        Some((d, q)) => is_strong_lucas_probable_prime(n, &d, &q),
+        None => false,
+    }
+}
+
+/// Strong Fermat (Miller–Rabin) test to base 2 for an odd `n > 2`.
+fn is_strong_probable_prime_base_2(n: &BigUint) -> bool {
+    let n_minus_one = n - 1u32;
+    let s = n_minus_one.trailing_zeros().unwrap_or(0);
+    let d = &n_minus_one >> s;
+
+    let mut x = BigUint::from(2u32).modpow(&d, n);
+    if x.is_one() || x == n_minus_one {
+        return true;
+    }
+    for _ in 1..s {
+        x = &x * &x % n;
+        if x == n_minus_one {
+            return true;
+        }
+        if x.is_one() {
+            return false;
+        }
+    }
+    false
+}
+
+/// Check whether `n` is a perfect square.
+fn is_perfect_square(n: &BigUint) -> bool {
+    let root = n.sqrt();
+    &root * &root == *n
+}
+
+/// Find Selfridge's parameters for the Lucas test (method A).
+///
+/// Searches D in the sequence 5, -7, 9, -11, ... for the first value with Jacobi symbol
+/// (D/n) = -1 and sets P = 1, Q = (1 - D) / 4. Both D and Q are returned reduced modulo n.
+///
+/// Returns `None` if a D sharing a non-trivial factor with `n` is found, proving `n` composite.
+fn selfridge_parameters(n: &BigUint) -> Option<(BigUint, BigUint)> {
+    let mut d: i64 = 5;
+    loop {
+        let d_mod_n = signed_mod(d, n);
+        match jacobi(&d_mod_n, n) {
+            -1 => {
+                let q = signed_mod((1 - d) / 4, n);
+                return Some((d_mod_n, q));
+            }
+            0 if BigUint::from(d.unsigned_abs()) != *n => return None,
+            _ => {}
+        }
+        d = if d > 0 { -(d + 2) } else { -d + 2 };
+    }
+}
+
+/// Strong Lucas probable-prime test for odd `n` with P = 1 and the given D and Q.
+#[allow(clippy::many_single_char_names)] // Mathematical notation: U, V, Q, D, n
+fn is_strong_lucas_probable_prime(n: &BigUint, d: &BigUint, q: &BigUint) -> bool {
+    // Write n + 1 = k * 2^s with k odd
+    let n_plus_one = n + 1u32;
+    let s = n_plus_one.trailing_zeros().unwrap_or(0);
+    let k = &n_plus_one >> s;
+
+    let (u, mut v, mut qk) = lucas_sequence_mod(&k, d, q, n);
+    if u.is_zero() || v.is_zero() {
+        return true;
+    }
+    for _ in 1..s {
+        // V(2k) = V(k)^2 - 2*Q^k
+        v = sub_mod(&(&v * &v % n), &((&qk << 1) % n), n);
+        if v.is_zero() {
+            return true;
+        }
+        qk = &qk * &qk % n;
+    }
+    false
+}
+
+/// Compute (U(k), V(k), Q^k) modulo `n` for the Lucas sequences with P = 1.
+///
+/// Scans the bits of `k` from the most significant one, doubling at every step and
+/// advancing by one whenever the bit is set, mirroring the fast doubling used for F(n).
+#[allow(clippy::many_single_char_names)] // Mathematical notation: U, V, Q, D, n
+fn lucas_sequence_mod(
+    k: &BigUint,
+    d: &BigUint,
+    q: &BigUint,
+    n: &BigUint,
+) -> (BigUint, BigUint, BigUint) {
+    // Start from U(1) = 1, V(1) = P = 1, Q^1 = Q
+    let mut u = BigUint::one();
+    let mut v = BigUint::one();
+    let mut qk = q.clone();
+
+    for bit in (0..k.bits().saturating_sub(1)).rev() {
+        // Doubling step: U(2j) = U(j) * V(j), V(2j) = V(j)^2 - 2*Q^j
+        u = &u * &v % n;
+        v = sub_mod(&(&v * &v % n), &((&qk << 1) % n), n);
+        qk = &qk * &qk % n;
+
+        if k.bit(bit) {
+            // Increment step: U(j+1) = (U(j) + V(j)) / 2, V(j+1) = (D*U(j) + V(j)) / 2
+            let next_u = half_mod(&(&u + &v), n);
+            let next_v = half_mod(&(d * &u + &v), n);
+            u = next_u;
+            v = next_v;
+            qk = &qk * q % n;
+        }
+    }
+
+    (u, v, qk)
+}
+
+/// Compute `x / 2 mod n` for odd `n`, where `x` may be as large as a few multiples of `n`.
+fn half_mod(x: &BigUint, n: &BigUint) -> BigUint {
+    if x.bit(0) {
+        ((x + n) >> 1) % n
+    } else {
+        (x >> 1) % n
+    }
+}
+
+/// Compute `(a - b) mod n` for `a, b < n`.
+fn sub_mod(a: &BigUint, b: &BigUint, n: &BigUint) -> BigUint {
+    if a >= b { a - b } else { n - b + a }
+}
+
+/// Reduce a signed machine integer modulo `n`.
+fn signed_mod(x: i64, n: &BigUint) -> BigUint {
+    let r = BigUint::from(x.unsigned_abs()) % n;
+    if x < 0 && !r.is_zero() { n - r } else { r }
+}
+
+/// Compute the Jacobi symbol (a/n) for odd `n`.
+fn jacobi(a: &BigUint, n: &BigUint) -> i32 {
+    let mut a = a % n;
+    let mut n = n.clone();
+    let mut result = 1;
+
+    while !a.is_zero() {
+        // Pull out factors of two using (2/n) = -1 iff n = 3 or 5 (mod 8)
+        let twos = a.trailing_zeros().unwrap_or(0);
+        a >>= twos;
+        let n_mod_8 = (&n % 8u32).to_u32_digits().first().copied().unwrap_or(0);
+        if twos % 2 == 1 && (n_mod_8 == 3 || n_mod_8 == 5) {
+            result = -result;
+        }
+
+        // Quadratic reciprocity: flip the sign if both are 3 (mod 4)
+        let a_mod_4 = (&a % 4u32).to_u32_digits().first().copied().unwrap_or(0);
+        if a_mod_4 == 3 && n_mod_8 % 4 == 3 {
+            result = -result;
+        }
+        std::mem::swap(&mut a, &mut n);
+        a %= &n;
+    }
+
+    if n.is_one() { result } else { 0 }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn is_prime_naive(n: u64) -> bool {
+        n >= 2 && (2..=n.isqrt()).all(|d| !n.is_multiple_of(d))
+    }
+
+    #[test]
+    fn matches_trial_division() {
+        for n in 0..20_000u64 {
+            assert_eq!(
+                is_probable_prime(&BigUint::from(n)),
+                is_prime_naive(n),
+                "mismatch for {n}"
+            );
+        }
+    }
+
+    #[test]
+    fn rejects_pseudoprimes() {
+        // Carmichael numbers, strong base-2 pseudoprimes and strong Lucas pseudoprimes
+        let pseudoprimes: [u64; 17] = [
+            561,
+            1105,
+            1729,
+            2465,
+            2821,
+            6601,
+            8911,
+            2047,
+            3277,
+            4033,
+            4681,
+            8321,
+            5459,
+            5777,
+            10877,
+            16109,
+            3_215_031_751,
+        ];
+        for n in pseudoprimes {
+            assert!(!is_probable_prime(&BigUint::from(n)), "{n} is composite");
+        }
+    }
+
+    #[test]
+    fn large_primes() {
+        let mersenne_127 = (BigUint::one() << 127) - 1u32;
+        assert!(is_probable_prime(&mersenne_127));
+        let mersenne_521 = (BigUint::one() << 521) - 1u32;
+        assert!(is_probable_prime(&mersenne_521));
+        // 2^128 + 1 = 59649589127497217 * 5704689200685129054721
+        assert!(!is_probable_prime(&((BigUint::one() << 128) + 1u32)));
+        assert!(!is_probable_prime(&(&mersenne_127 * &mersenne_127)));
+    }
+
+    #[test]
+    fn jacobi_symbol() {
+        // (2/7) = 1, (3/7) = -1, (5/21) = 1, (7/21) = 0
+        assert_eq!(jacobi(&BigUint::from(2u32), &BigUint::from(7u32)), 1);
+        assert_eq!(jacobi(&BigUint::from(3u32), &BigUint::from(7u32)), -1);
+        assert_eq!(jacobi(&BigUint::from(5u32), &BigUint::from(21u32)), 1);
+        assert_eq!(jacobi(&BigUint::from(7u32), &BigUint::from(21u32)), 0);
+    }
+}
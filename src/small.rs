@@ -0,0 +1,134 @@
+//! Fibonacci numbers that fit in primitive integers.
+//!
+//! F(186) is the largest Fibonacci number below 2^128, so every value that fits in a primitive
+//! integer is looked up in a table built at compile time. Small indices take this path
+//! throughout the crate instead of allocating big integers for intermediate values.
+
+use crate::{Backend, Fib};
+
+/// Number of Fibonacci numbers that fit in a `u128`, F(0) through F(186).
+const TABLE_LEN: usize = 187;
+
+/// F(0) through F(186), computed at compile time.
+const TABLE: [u128; TABLE_LEN] = {
+    let mut table = [0; TABLE_LEN];
+    table[1] = 1;
+    let mut i = 2;
+    while i < TABLE_LEN {
+        table[i] = table[i - 1] + table[i - 2];
+        i += 1;
+    }
+    table
+};
+
+impl Fib {
+    /// Calculate the nth Fibonacci number as a `u64`, if it fits.
+    ///
+    /// # Arguments
+    ///
+    /// * `n` - The index of the Fibonacci number to calculate
+    ///
+    /// # Returns
+    ///
+    /// * `Some(F(n))` for n up to 93, `None` for larger indices
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use fib_rs::Fib;
+    ///
+    /// assert_eq!(Fib::single_u64(93), Some(12_200_160_415_121_876_738));
+    /// assert_eq!(Fib::single_u64(94), None);
+    /// ```
+    #[must_use]
+    pub fn single_u64(n: u128) -> Option<u64> {
+        Self::checked_single(n)
+    }
+
+    /// Calculate the nth Fibonacci number as a `u128`, if it fits.
+    ///
+    /// # Arguments
+    ///
+    /// * `n` - The index of the Fibonacci number to calculate
+    ///
+    /// # Returns
+    ///
+    /// * `Some(F(n))` for n up to 186, `None` for larger indices
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use fib_rs::Fib;
+    ///
+    /// assert_eq!(Fib::single_u128(100), Some(354_224_848_179_261_915_075));
+    /// assert_eq!(Fib::single_u128(187), None);
+    /// ```
+    #[must_use]
+    pub fn single_u128(n: u128) -> Option<u128> {
+        usize::try_from(n).ok().and_then(|n| TABLE.get(n)).copied()
+    }
+
+    /// Calculate the nth Fibonacci number as any primitive integer type, if it fits.
+    ///
+    /// # Arguments
+    ///
+    /// * `n` - The index of the Fibonacci number to calculate
+    ///
+    /// # Returns
+    ///
+    /// * `Some(F(n))` if F(n) is representable as a `T`, `None` otherwise
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use fib_rs::Fib;
+    ///
+    /// assert_eq!(Fib::checked_single::<u8>(13), Some(233));
+    /// assert_eq!(Fib::checked_single::<u8>(14), None);
+    /// assert_eq!(Fib::checked_single::<i32>(46), Some(1_836_311_903));
+    /// ```
+    #[must_use]
+    pub fn checked_single<T: TryFrom<u128>>(n: u128) -> Option<T> {
+        Self::single_u128(n).and_then(|value| T::try_from(value).ok())
+    }
+}
+
+/// The pair (F(n), F(n+1)) from the table, if both fit in a `u128`.
+pub(crate) fn pair<B: Backend>(n: u128) -> Option<(B, B)> {
+    let next = Fib::single_u128(n.checked_add(1)?)?;
+    Some((B::from_u128(Fib::single_u128(n)?), B::from_u128(next)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn table_matches_naive() {
+        for n in 0..=186 {
+            assert_eq!(
+                Fib::single_u128(n).map(num_bigint::BigUint::from),
+                Some(crate::Algorithm::Naive.single(n)),
+                "F({n})"
+            );
+        }
+    }
+
+    #[test]
+    fn primitive_limits() {
+        assert_eq!(
+            Fib::single_u128(186),
+            Some(332_825_110_087_067_562_321_196_029_789_634_457_848)
+        );
+        assert_eq!(Fib::single_u128(u128::MAX), None);
+        assert_eq!(Fib::single_u64(0), Some(0));
+        assert_eq!(Fib::checked_single::<u16>(24), Some(46_368));
+        assert_eq!(Fib::checked_single::<u16>(25), None);
+        assert_eq!(
+            Fib::checked_single::<i64>(92),
+            Some(7_540_113_804_746_346_429)
+        );
+        assert_eq!(Fib::checked_single::<i64>(93), None);
+        assert_eq!(Fib::checked_single::<u64>(93), Fib::single_u64(93));
+    }
+}
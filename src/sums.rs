@@ -0,0 +1,335 @@
+//! Closed-form aggregates over ranges of Fibonacci numbers.
+//!
+//! Every aggregate is obtained from prefix identities such as
+//!
+//! - Σ F(i) for i in 0..=n = F(n+2) - 1
+//! - Σ F(i)^2 for i in 0..=n = F(n) * F(n+1)
+//!
+//! so an interval costs a constant number of fast doubling calls, independent of its length.
+//! The two endpoints are evaluated in parallel.
+
+use num_bigint::{BigInt, BigUint, Sign};
+use num_traits::{One, Zero};
+use rayon::join;
+
+use crate::Fib;
+
+impl Fib {
+    /// Calculate the sum of the Fibonacci numbers F(start) through F(end).
+    ///
+    /// Uses Σ F(i) for i in 0..=n = F(n+2) - 1, so the sum over the interval is F(end+2) - F(start+1).
+    ///
+    /// # Arguments
+    ///
+    /// * `start` - The starting index of the range
+    /// * `end` - The ending index of the range (inclusive)
+    ///
+    /// # Returns
+    ///
+    /// * The sum as a `BigUint`, or zero if `end < start`
+    ///
+    /// # Panics
+    ///
+    /// Panics if `end + 2` overflows `u128`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use fib_rs::Fib;
+    /// use num_bigint::BigUint;
+    ///
+    /// // F(3) + F(4) + F(5) = 2 + 3 + 5
+    /// assert_eq!(Fib::sum(3, 5), BigUint::from(10u32));
+    /// ```
+    #[must_use]
+    pub fn sum(start: u128, end: u128) -> BigUint {
+        if end < start {
+            return BigUint::zero();
+        }
+        let top = end.checked_add(2).expect("index overflows u128");
+        let (upper, lower) = join(|| Self::single(top), || Self::single(start + 1));
+        upper - lower
+    }
+
+    /// Calculate the sum of the squares F(start)^2 through F(end)^2.
+    ///
+    /// Uses Σ F(i)^2 for i in 0..=n = F(n) * F(n+1).
+    ///
+    /// # Arguments
+    ///
+    /// * `start` - The starting index of the range
+    /// * `end` - The ending index of the range (inclusive)
+    ///
+    /// # Returns
+    ///
+    /// * The sum of squares as a `BigUint`, or zero if `end < start`
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use fib_rs::Fib;
+    /// use num_bigint::BigUint;
+    ///
+    /// // 1^2 + 2^2 + 3^2 + 5^2
+    /// assert_eq!(Fib::sum_of_squares(2, 5), BigUint::from(39u32));
+    /// ```
+    #[must_use]
+    pub fn sum_of_squares(start: u128, end: u128) -> BigUint {
+        if end < start {
+            return BigUint::zero();
+        }
+        let prefix = |n: Option<u128>| {
+            n.map_or_else(BigUint::zero, |n| {
+                let (fk, fk1) = Self::fib_fast_doubling_helper(n);
+                fk * fk1
+            })
+        };
+        let (upper, lower) = join(|| prefix(Some(end)), || prefix(start.checked_sub(1)));
+        upper - lower
+    }
+
+    /// Calculate the weighted sum start*F(start) + ... + end*F(end).
+    ///
+    /// Uses Σ i*F(i) for i in 0..=n = n*F(n+2) - F(n+3) + 2.
+    ///
+    /// # Arguments
+    ///
+    /// * `start` - The starting index of the range
+    /// * `end` - The ending index of the range (inclusive)
+    ///
+    /// # Returns
+    ///
+    /// * The weighted sum as a `BigUint`, or zero if `end < start`
+    ///
+    /// # Panics
+    ///
+    /// Panics if `end + 2` overflows `u128`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use fib_rs::Fib;
+    /// use num_bigint::BigUint;
+    ///
+    /// // 1*1 + 2*1 + 3*2 + 4*3
+    /// assert_eq!(Fib::weighted_sum(1, 4), BigUint::from(21u32));
+    /// ```
+    #[must_use]
+    pub fn weighted_sum(start: u128, end: u128) -> BigUint {
+        if end < start {
+            return BigUint::zero();
+        }
+        assert!(end.checked_add(2).is_some(), "index overflows u128");
+        let prefix = |n: Option<u128>| {
+            n.map_or_else(BigUint::zero, |n| {
+                let (fn2, fn3) = Self::fib_fast_doubling_helper(n + 2);
+                fn2 * n + 2u32 - fn3
+            })
+        };
+        let (upper, lower) = join(|| prefix(Some(end)), || prefix(start.checked_sub(1)));
+        upper - lower
+    }
+
+    /// Calculate the alternating sum (-1)^start*F(start) + ... + (-1)^end*F(end).
+    ///
+    /// Uses Σ (-1)^i*F(i) for i in 0..=n = (-1)^n*F(n-1) - 1.
+    ///
+    /// # Arguments
+    ///
+    /// * `start` - The starting index of the range
+    /// * `end` - The ending index of the range (inclusive)
+    ///
+    /// # Returns
+    ///
+    /// * The alternating sum as a signed `BigInt`, or zero if `end < start`
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use fib_rs::Fib;
+    /// use num_bigint::BigInt;
+    ///
+    /// // -F(1) + F(2) - F(3) + F(4) = -1 + 1 - 2 + 3
+    /// assert_eq!(Fib::alternating_sum(1, 4), BigInt::from(1));
+    /// ```
+    #[must_use]
+    pub fn alternating_sum(start: u128, end: u128) -> BigInt {
+        if end < start {
+            return BigInt::zero();
+        }
+        let prefix = |n: Option<u128>| match n {
+            None | Some(0) => BigInt::zero(),
+            Some(n) => {
+                let fn_1 = BigInt::from(Self::single(n - 1));
+                if n.is_multiple_of(2) {
+                    fn_1 - 1
+                } else {
+                    -fn_1 - 1
+                }
+            }
+        };
+        let (upper, lower) = join(|| prefix(Some(end)), || prefix(start.checked_sub(1)));
+        upper - lower
+    }
+
+    /// Calculate the sum of products F(start)*F(start+k) + ... + F(end)*F(end+k).
+    ///
+    /// Uses 5*F(i)*F(i+k) = L(2i+k) - (-1)^i*L(k), whose Lucas terms telescope to give
+    /// Σ F(i)*F(i+k) for i in start..=end = (L(2*end+k+1) - L(2*start+k-1) - c*L(k)) / 5,
+    /// where c = [end is even] - [start - 1 is even].
+    ///
+    /// # Arguments
+    ///
+    /// * `start` - The starting index of the range
+    /// * `end` - The ending index of the range (inclusive)
+    /// * `k` - The offset between the two factors of each product
+    ///
+    /// # Returns
+    ///
+    /// * The sum of products as a `BigUint`, or zero if `end < start`
+    ///
+    /// # Panics
+    ///
+    /// Panics if `2 * end + k + 1` overflows `u128`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use fib_rs::Fib;
+    /// use num_bigint::BigUint;
+    ///
+    /// // F(1)*F(3) + F(2)*F(4) + F(3)*F(5) = 2 + 3 + 10
+    /// assert_eq!(Fib::sum_of_products(1, 3, 2), BigUint::from(15u32));
+    ///
+    /// // With k = 0 this is the sum of squares
+    /// assert_eq!(Fib::sum_of_products(0, 50, 0), Fib::sum_of_squares(0, 50));
+    /// ```
+    #[must_use]
+    pub fn sum_of_products(start: u128, end: u128, k: u128) -> BigUint {
+        if end < start {
+            return BigUint::zero();
+        }
+        let top = end
+            .checked_mul(2)
+            .and_then(|m| m.checked_add(k))
+            .and_then(|m| m.checked_add(1))
+            .expect("index overflows u128");
+        let ((upper, lower), lk) = join(
+            || {
+                join(
+                    || BigInt::from(Self::lucas(top)),
+                    // L(-1) = -1 is the only negative index reachable here
+                    || match (2 * start + k).checked_sub(1) {
+                        Some(m) => BigInt::from(Self::lucas(m)),
+                        None => -BigInt::one(),
+                    },
+                )
+            },
+            || BigInt::from(Self::lucas(k)),
+        );
+
+        // Parity term: [end is even] - [start - 1 is even], where start - 1 = -1 counts as odd
+        let mut total = upper - lower;
+        let end_even = end.is_multiple_of(2);
+        let before_start_even = start % 2 == 1;
+        match (end_even, before_start_even) {
+            (true, false) => total -= lk,
+            (false, true) => total += lk,
+            _ => {}
+        }
+
+        // A sum of products of Fibonacci numbers is never negative
+        debug_assert!(total.sign() != Sign::Minus);
+        total.into_parts().1 / 5u32
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RANGES: [(u128, u128); 8] = [
+        (0, 0),
+        (0, 1),
+        (1, 1),
+        (0, 30),
+        (1, 30),
+        (2, 31),
+        (17, 64),
+        (100, 160),
+    ];
+
+    fn signed(i: u128) -> BigInt {
+        if i.is_multiple_of(2) {
+            BigInt::from(Fib::single(i))
+        } else {
+            -BigInt::from(Fib::single(i))
+        }
+    }
+
+    #[test]
+    fn sums_match_brute_force() {
+        for (start, end) in RANGES {
+            let values = Fib::range(start, end);
+            assert_eq!(Fib::sum(start, end), values.iter().sum::<BigUint>());
+            assert_eq!(
+                Fib::sum_of_squares(start, end),
+                values.iter().map(|v| v * v).sum::<BigUint>()
+            );
+            assert_eq!(
+                Fib::weighted_sum(start, end),
+                (start..=end)
+                    .zip(&values)
+                    .map(|(i, v)| v * i)
+                    .sum::<BigUint>()
+            );
+            assert_eq!(
+                Fib::alternating_sum(start, end),
+                (start..=end).map(signed).sum::<BigInt>()
+            );
+        }
+    }
+
+    #[test]
+    fn sum_of_products_matches_brute_force() {
+        for (start, end) in RANGES {
+            for k in 0..6 {
+                let expected = (start..=end)
+                    .map(|i| Fib::single(i) * Fib::single(i + k))
+                    .sum::<BigUint>();
+                assert_eq!(
+                    Fib::sum_of_products(start, end, k),
+                    expected,
+                    "{start}..={end}, k = {k}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "index overflows u128")]
+    fn sum_rejects_overflowing_index() {
+        let _ = Fib::sum(0, u128::MAX);
+    }
+
+    #[test]
+    #[should_panic(expected = "index overflows u128")]
+    fn weighted_sum_rejects_overflowing_index() {
+        let _ = Fib::weighted_sum(0, u128::MAX - 1);
+    }
+
+    #[test]
+    #[should_panic(expected = "index overflows u128")]
+    fn sum_of_products_rejects_overflowing_index() {
+        let _ = Fib::sum_of_products(0, u128::MAX / 2, 1);
+    }
+
+    #[test]
+    fn inverted_ranges_are_empty() {
+        assert!(Fib::sum(10, 5).is_zero());
+        assert!(Fib::sum_of_squares(10, 5).is_zero());
+        assert!(Fib::weighted_sum(10, 5).is_zero());
+        assert!(Fib::alternating_sum(10, 5).is_zero());
+        assert!(Fib::sum_of_products(10, 5, 3).is_zero());
+    }
+}
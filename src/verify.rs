@@ -0,0 +1,197 @@
+//! Independent correctness checks of computed Fibonacci numbers.
+//!
+//! A claimed value of F(n) is reduced modulo several random primes between 2^60 and 2^61 and
+//! compared with F(n) mod p computed directly by modular fast doubling, which takes O(log n)
+//! word operations and shares no big-integer code with the full computation.
+//!
+//! A wrong value passes only if its difference from F(n) is divisible by every chosen prime.
+//! The difference has at most about 0.7n bits, so at most 0.7n / 60 of the primes near 2^61
+//! divide it, and with primes chosen at random the chance of a false pass is negligible.
+
+use std::hash::{BuildHasher, RandomState};
+
+use num_bigint::BigUint;
+use num_traits::ToPrimitive;
+use rayon::prelude::*;
+
+use crate::{
+    Fib, FibError,
+    modular::{fib_mod, mul_mod, pow_mod},
+};
+
+/// Number of random primes each value is checked against.
+const VERIFY_PRIMES: usize = 8;
+
+/// Bases making Miller-Rabin deterministic for every 64-bit integer.
+const MILLER_RABIN_BASES: [u64; 12] = [2, 3, 5, 7, 11, 13, 17, 19, 23, 29, 31, 37];
+
+impl Fib {
+    /// Check a claimed value of F(n) against F(n) modulo several random word-sized primes.
+    ///
+    /// The check costs one pass over `value` per prime plus O(log n) word operations, far
+    /// less than recomputing F(n).
+    ///
+    /// # Arguments
+    ///
+    /// * `n` - The index of the Fibonacci number
+    /// * `value` - The claimed value of F(n)
+    ///
+    /// # Returns
+    ///
+    /// * `false` if `value` is certainly not F(n), `true` if it matched every residue
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use fib_rs::Fib;
+    ///
+    /// let value = Fib::single(100_000);
+    /// assert!(Fib::verify(100_000, &value));
+    /// assert!(!Fib::verify(100_000, &(value + 1u32)));
+    /// ```
+    #[must_use]
+    pub fn verify(n: u128, value: &BigUint) -> bool {
+        // F(n) has at least 0.69(n - 2) bits, so a far too short value fails without any division
+        if n > 2 && u128::from(value.bits()) < (n - 2) / 100 * 69 {
+            return false;
+        }
+        random_primes(VERIFY_PRIMES)
+            .into_par_iter()
+            .all(|p| (value % p).to_u64() == Some(fib_mod(n, p)))
+    }
+
+    /// Calculate the nth Fibonacci number and check it with [`Fib::verify`] before returning.
+    ///
+    /// # Arguments
+    ///
+    /// * `n` - The index of the Fibonacci number to calculate
+    ///
+    /// # Returns
+    ///
+    /// * The nth Fibonacci number as a `BigUint`
+    ///
+    /// # Errors
+    ///
+    /// * `FibError::VerificationFailed` if the computed value fails the check, which indicates
+    ///   a bug or a hardware fault
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use fib_rs::Fib;
+    ///
+    /// assert_eq!(Fib::single_verified(1_000).unwrap(), Fib::single(1_000));
+    /// ```
+    pub fn single_verified(n: u128) -> Result<BigUint, FibError> {
+        check(n, Self::single(n))
+    }
+}
+
+/// Return `value` if it passes [`Fib::verify`] for index `n`.
+pub(crate) fn check(n: u128, value: BigUint) -> Result<BigUint, FibError> {
+    if Fib::verify(n, &value) {
+        Ok(value)
+    } else {
+        Err(FibError::VerificationFailed { n })
+    }
+}
+
+/// Draw `count` distinct random primes between 2^60 and 2^61.
+fn random_primes(count: usize) -> Vec<u64> {
+    // RandomState is seeded from the operating system, which is all the randomness needed here
+    let mut state = RandomState::new().hash_one(0u64);
+    let mut primes = Vec::with_capacity(count);
+    while primes.len() < count {
+        let candidate = (splitmix64(&mut state) >> 4) | (1 << 60) | 1;
+        if is_prime_u64(candidate) && !primes.contains(&candidate) {
+            primes.push(candidate);
+        }
+    }
+    primes
+}
+
+/// Advance a `SplitMix64` generator and return its next output.
+fn splitmix64(state: &mut u64) -> u64 {
+    *state = state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+    let mut z = *state;
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^ (z >> 31)
+}
+
+/// Deterministic Miller-Rabin test for 64-bit integers.
+fn is_prime_u64(n: u64) -> bool {
+    if n < 2 {
+        return false;
+    }
+    for p in MILLER_RABIN_BASES {
+        if n.is_multiple_of(p) {
+            return n == p;
+        }
+    }
+    let s = (n - 1).trailing_zeros();
+    let d = (n - 1) >> s;
+    MILLER_RABIN_BASES.iter().all(|&a| {
+        let mut x = pow_mod(a, u128::from(d), n);
+        if x == 1 || x == n - 1 {
+            return true;
+        }
+        for _ in 1..s {
+            x = mul_mod(x, x, n);
+            if x == n - 1 {
+                return true;
+            }
+        }
+        false
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn small_primes() {
+        let sieve: Vec<u64> = (0..2_000)
+            .filter(|&n: &u64| n >= 2 && (2..=n.isqrt()).all(|d| !n.is_multiple_of(d)))
+            .collect();
+        let tested: Vec<u64> = (0..2_000).filter(|&n| is_prime_u64(n)).collect();
+        assert_eq!(tested, sieve);
+        // Strong pseudoprime to the first several bases
+        assert!(!is_prime_u64(3_825_123_056_546_413_051));
+        assert!(is_prime_u64((1 << 61) - 1));
+    }
+
+    #[test]
+    fn random_primes_are_distinct_primes() {
+        let primes = random_primes(VERIFY_PRIMES);
+        assert_eq!(primes.len(), VERIFY_PRIMES);
+        for (i, &p) in primes.iter().enumerate() {
+            assert!(p >> 60 == 1 && is_prime_u64(p));
+            assert!(!primes[..i].contains(&p));
+        }
+    }
+
+    #[test]
+    fn verification() {
+        for n in [0, 1, 2, 3, 50, 1_000, 54_321] {
+            let value = Fib::single(n);
+            assert!(Fib::verify(n, &value), "F({n})");
+            assert!(
+                !Fib::verify(n + 1, &value) || n < 2,
+                "F({n}) as F({})",
+                n + 1
+            );
+            assert!(!Fib::verify(n, &(value + 1u32)));
+        }
+        // Flipping one bit deep inside a large value is caught
+        let mut value = Fib::single(200_000);
+        value.set_bit(70_000, !value.bit(70_000));
+        assert!(!Fib::verify(200_000, &value));
+        assert!(!Fib::verify(200_000, &BigUint::from(5u32)));
+        assert_eq!(
+            check(10, BigUint::from(54u32)),
+            Err(FibError::VerificationFailed { n: 10 })
+        );
+    }
+}
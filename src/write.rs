@@ -0,0 +1,268 @@
+//! Streaming range output with bounded memory.
+//!
+//! [`Fib::write_range`] formats a range in windows of chunks: the chunks of one window are
+//! generated and formatted in parallel while the previous window is written, so at most two
+//! windows of text are held at once however long the range is. Chunks are sized by the bits they
+//! hold rather than by count, and each window is seeded from the state the previous one ended in.
+
+use std::io::{self, Write};
+
+use num_bigint::BigUint;
+use rayon::{current_num_threads, prelude::*};
+
+use crate::{
+    Control, Fib, FibError, FibPair, engine::balanced_bounds, error, estimate::LOG2_PHI_UPPER,
+    write_decimal,
+};
+
+/// Number of bits of values formatted per chunk, 2^25 or about 10 MB of decimal text.
+const WRITE_CHUNK_BITS: f64 = 33_554_432.0;
+
+/// Number of chunks per thread formatted in each window.
+const WRITE_WINDOW_PER_THREAD: usize = 2;
+
+/// The pair (F(m), F(m+1)) together with its index m.
+type IndexedPair = (u128, FibPair);
+
+/// Layout of each line written by [`Fib::write_range`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum RangeFormat {
+    /// `F(n) = value`, as printed by the `fib range` command
+    #[default]
+    Labeled,
+    /// The value alone
+    Plain,
+    /// `n,value`
+    Csv,
+}
+
+impl RangeFormat {
+    /// Append the line for F(n) to `out`.
+    fn push_line(self, out: &mut Vec<u8>, n: u128, value: &BigUint) {
+        // Writing into a Vec cannot fail
+        let _ = match self {
+            Self::Labeled => write!(out, "F({n}) = "),
+            Self::Plain => Ok(()),
+            Self::Csv => write!(out, "{n},"),
+        };
+        let _ = write_decimal(value, &mut *out);
+        out.push(b'\n');
+    }
+}
+
+impl Fib {
+    /// Write Fibonacci numbers for indices in the given inclusive range to `writer`, one line
+    /// per value in ascending order of index.
+    ///
+    /// Chunks of the range are computed and formatted in parallel, but only a window of a few
+    /// chunks per thread is held in memory at a time, so the output may be far larger than RAM.
+    /// The writer is flushed once all values are written.
+    ///
+    /// # Arguments
+    ///
+    /// * `start` - The starting index of the range
+    /// * `end` - The ending index of the range (inclusive)
+    /// * `writer` - The destination of the formatted values
+    /// * `format` - The layout of each line
+    ///
+    /// # Errors
+    ///
+    /// * `FibError::InvertedRange` if `end < start`
+    /// * `FibError::CountOverflow` if the number of values does not fit in `usize`
+    /// * `FibError::Io` if writing to `writer` fails
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use fib_rs::{Fib, RangeFormat};
+    ///
+    /// let mut out = Vec::new();
+    /// Fib::write_range(10, 12, &mut out, RangeFormat::Labeled).unwrap();
+    /// assert_eq!(out, b"F(10) = 55\nF(11) = 89\nF(12) = 144\n");
+    ///
+    /// let mut csv = Vec::new();
+    /// Fib::write_range(0, 2, &mut csv, RangeFormat::Csv).unwrap();
+    /// assert_eq!(csv, b"0,0\n1,1\n2,1\n");
+    /// ```
+    pub fn write_range<W: Write + Send>(
+        start: u128,
+        end: u128,
+        mut writer: W,
+        format: RangeFormat,
+    ) -> Result<(), FibError> {
+        let total_count = error::range_len(start, end)?;
+        let control = Control::new();
+        let bounds = write_bounds(start, total_count);
+        let window_len = current_num_threads().saturating_mul(WRITE_WINDOW_PER_THREAD);
+
+        // Format each window while the previous one is written out
+        let mut carried: Option<IndexedPair> = None;
+        let mut pending: Vec<Vec<u8>> = Vec::new();
+        for window in bounds.windows(2).collect::<Vec<_>>().chunks(window_len) {
+            let chunks: Vec<(u128, usize)> = window
+                .iter()
+                .map(|w| (start + w[0] as u128, w[1] - w[0]))
+                .collect();
+            let (written, formatted) = rayon::join(
+                || write_all(&mut writer, &pending),
+                || format_window(&chunks, carried.take(), format, &control),
+            );
+            written?;
+            let (texts, next) = formatted?;
+            pending = texts;
+            carried = Some(next);
+        }
+        write_all(&mut writer, &pending)?;
+        writer.flush()?;
+        Ok(())
+    }
+}
+
+/// Chunk offsets for a range of `total_count` values from index `start`, with about
+/// [`WRITE_CHUNK_BITS`] bits per chunk and at least one chunk per thread.
+#[allow(
+    clippy::cast_precision_loss,
+    clippy::cast_possible_truncation,
+    clippy::cast_sign_loss
+)] // The chunk count only needs to be approximate
+fn write_bounds(start: u128, total_count: usize) -> Vec<usize> {
+    let count = total_count as f64;
+    let bits = (start as f64 + count / 2.0) * LOG2_PHI_UPPER * count;
+    let chunks = ((bits / WRITE_CHUNK_BITS).ceil() as usize).max(current_num_threads());
+    balanced_bounds(start, total_count, chunks)
+}
+
+/// Generate and format the chunks `(chunk_start, len)` of one window in parallel.
+///
+/// Returns the text of every chunk along with the pair (F(m), F(m+1)) for the index m following
+/// the window, from which the next window is seeded.
+fn format_window(
+    chunks: &[(u128, usize)],
+    carried: Option<IndexedPair>,
+    format: RangeFormat,
+    control: &Control,
+) -> Result<(Vec<Vec<u8>>, IndexedPair), FibError> {
+    let starts: Vec<u128> = chunks.iter().map(|&(chunk_start, _)| chunk_start).collect();
+    let mut seeds = match carried {
+        // The previous window ended exactly where this one starts
+        Some((index, pair)) if index == starts[0] => {
+            let mut seeds = vec![pair];
+            let rest = Fib::chunk_seeds(&starts[1..], seeds.last().map(|p| (index, p)), control)?;
+            seeds.extend(rest);
+            seeds
+        }
+        _ => Fib::chunk_seeds(&starts, None, control)?,
+    };
+    let (&(last_start, last_len), rest) = chunks.split_last().expect("every window has a chunk");
+    let last_seed = seeds.pop().expect("every window has a chunk");
+
+    let (mut texts, (last_text, next)) = rayon::join(
+        || {
+            rest.par_iter()
+                .zip(seeds)
+                .map(|(&(chunk_start, len), seed)| format_chunk(chunk_start, len, seed, format).0)
+                .collect::<Vec<_>>()
+        },
+        || format_chunk(last_start, last_len, last_seed, format),
+    );
+    texts.push(last_text);
+    Ok((texts, (last_start + last_len as u128, next)))
+}
+
+/// Format `len` values from index `chunk_start`, starting from the seed (F(m), F(m+1)).
+///
+/// Returns the text along with the pair following the chunk.
+fn format_chunk(
+    chunk_start: u128,
+    len: usize,
+    (mut a, mut b): FibPair,
+    format: RangeFormat,
+) -> (Vec<u8>, FibPair) {
+    let mut text = Vec::new();
+    for n in (chunk_start..).take(len) {
+        format.push_line(&mut text, n, &a);
+        let next = &a + &b;
+        a = std::mem::replace(&mut b, next);
+    }
+    (text, (a, b))
+}
+
+/// Write every buffer in order.
+fn write_all(writer: &mut impl Write, texts: &[Vec<u8>]) -> io::Result<()> {
+    texts.iter().try_for_each(|text| writer.write_all(text))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The expected output of `write_range` built from `Fib::range`.
+    fn expected(start: u128, end: u128, format: RangeFormat) -> Vec<u8> {
+        let mut out = Vec::new();
+        for (n, value) in (start..).zip(Fib::range(start, end)) {
+            format.push_line(&mut out, n, &value);
+        }
+        out
+    }
+
+    #[test]
+    fn writes_match_range() {
+        let pool = rayon::ThreadPoolBuilder::new()
+            .num_threads(3)
+            .build()
+            .unwrap();
+        pool.install(|| {
+            for (start, end) in [(0, 0), (0, 50), (7, 7), (1_000, 2_000)] {
+                for format in [RangeFormat::Labeled, RangeFormat::Plain, RangeFormat::Csv] {
+                    let mut out = Vec::new();
+                    Fib::write_range(start, end, &mut out, format).unwrap();
+                    assert_eq!(out, expected(start, end, format), "{start}..={end}");
+                }
+            }
+        });
+    }
+
+    #[test]
+    fn windows_carry_their_seeds() {
+        // Two consecutive windows, the second seeded from the first
+        let chunks = [(100, 5), (105, 7)];
+        let (texts, next) =
+            format_window(&chunks, None, RangeFormat::Plain, &Control::new()).unwrap();
+        assert_eq!(texts.concat(), expected(100, 111, RangeFormat::Plain));
+        assert_eq!(next, (112, (Fib::single(112), Fib::single(113))));
+
+        let chunks = [(112, 3), (115, 1)];
+        let (texts, next) =
+            format_window(&chunks, Some(next), RangeFormat::Plain, &Control::new()).unwrap();
+        assert_eq!(texts.concat(), expected(112, 115, RangeFormat::Plain));
+        assert_eq!(next.0, 116);
+    }
+
+    /// A writer whose reader has gone away.
+    struct Failing;
+
+    impl Write for Failing {
+        fn write(&mut self, _: &[u8]) -> io::Result<usize> {
+            Err(io::Error::new(io::ErrorKind::BrokenPipe, "closed"))
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn write_errors() {
+        assert_eq!(
+            Fib::write_range(5, 4, io::sink(), RangeFormat::Plain),
+            Err(FibError::InvertedRange { start: 5, end: 4 })
+        );
+        assert!(matches!(
+            Fib::write_range(0, 10, Failing, RangeFormat::Plain),
+            Err(FibError::Io {
+                kind: io::ErrorKind::BrokenPipe,
+                ..
+            })
+        ));
+    }
+}
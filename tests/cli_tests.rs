@@ -21,6 +21,18 @@ fn test_range_fibonacci() {
         .stdout(predicate::str::contains("F(7) = 13"));
 }
 
+#[test]
+fn test_range_streams_in_order() {
+    let mut expected = Vec::new();
+    fib_rs::Fib::write_range(0, 300, &mut expected, fib_rs::RangeFormat::Labeled).unwrap();
+    assert!(expected.starts_with(b"F(0) = 0\nF(1) = 1\nF(2) = 1\n"));
+    cargo_bin_cmd!("fib")
+        .args(["range", "0", "300"])
+        .assert()
+        .success()
+        .stdout(expected);
+}
+
 #[test]
 fn test_range_invalid() {
     cargo_bin_cmd!("fib")
@@ -29,3 +41,58 @@ fn test_range_invalid() {
         .success()
         .stderr(predicate::str::contains("Invalid range: end < start"));
 }
+
+#[test]
+#[cfg(target_os = "linux")]
+fn test_write_errors_fail() {
+    // Writes to /dev/full fail with "no space left on device"
+    for args in [&["single", "1000"][..], &["range", "0", "1000"]] {
+        let output = std::process::Command::new(env!("CARGO_BIN_EXE_fib"))
+            .args(args)
+            .stdout(std::fs::File::create("/dev/full").unwrap())
+            .output()
+            .unwrap();
+        assert_eq!(output.status.code(), Some(1), "{args:?}");
+        assert!(!output.stderr.is_empty(), "{args:?}");
+    }
+}
+
+#[test]
+fn test_single_algorithms() {
+    for algorithm in ["naive", "matrix", "fast-doubling", "lucas"] {
+        cargo_bin_cmd!("fib")
+            .args(["single", "100", "--algorithm", algorithm])
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("F(100) = 354224848179261915075"));
+    }
+}
+
+#[test]
+fn test_single_unknown_algorithm() {
+    cargo_bin_cmd!("fib")
+        .args(["single", "10", "--algorithm", "bogus"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("unknown algorithm `bogus`"));
+}
+
+#[test]
+fn test_file_output() {
+    let dir = std::env::temp_dir().join(format!("fib-rs-cli-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let output = dir.join("f5000.txt");
+    cargo_bin_cmd!("fib")
+        .args(["file", "5000"])
+        .arg(&output)
+        .arg("--scratch-dir")
+        .arg(dir.join("scratch"))
+        .args(["--memory-limit", "0"])
+        .assert()
+        .success();
+    assert_eq!(
+        std::fs::read_to_string(&output).unwrap(),
+        fib_rs::Fib::single(5000).to_string()
+    );
+    std::fs::remove_dir_all(dir).unwrap();
+}
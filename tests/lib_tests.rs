@@ -1,4 +1,4 @@
-use fib_rs::Fib;
+use fib_rs::{Fib, FibError, is_probable_prime};
 
 #[test]
 fn test_fibonacci_identities() {
@@ -24,3 +24,31 @@ fn test_invalid_range_returns_empty() {
     let range_results = Fib::range(10, 5);
     assert!(range_results.is_empty());
 }
+
+#[test]
+fn test_fibonacci_primes() {
+    // Indices n for which F(n) is prime
+    let prime_indices = [
+        3, 4, 5, 7, 11, 13, 17, 23, 29, 43, 47, 83, 131, 137, 359, 431, 433, 449,
+    ];
+    for n in 0..=450 {
+        assert_eq!(
+            is_probable_prime(&Fib::single(n)),
+            prime_indices.contains(&n),
+            "F({n})"
+        );
+    }
+}
+
+#[test]
+fn test_try_range_errors() {
+    assert_eq!(Fib::try_range(5, 7).unwrap(), Fib::range(5, 7));
+    assert_eq!(
+        Fib::try_range(10, 5),
+        Err(FibError::InvertedRange { start: 10, end: 5 })
+    );
+    assert!(matches!(
+        Fib::try_range(1, u128::MAX),
+        Err(FibError::CountOverflow { .. })
+    ));
+}
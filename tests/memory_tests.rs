@@ -0,0 +1,56 @@
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+use fib_rs::Fib;
+
+/// Allocator tracking the current and peak number of live heap bytes.
+struct PeakAllocator;
+
+static CURRENT: AtomicUsize = AtomicUsize::new(0);
+static PEAK: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for PeakAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let current = CURRENT.fetch_add(layout.size(), Ordering::Relaxed) + layout.size();
+        PEAK.fetch_max(current, Ordering::Relaxed);
+        unsafe { System.alloc(layout) }
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        CURRENT.fetch_sub(layout.size(), Ordering::Relaxed);
+        unsafe { System.dealloc(ptr, layout) }
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: PeakAllocator = PeakAllocator;
+
+#[test]
+fn test_single_peak_memory() {
+    // The only test in this binary, so no other allocations interfere with the measurement
+    for threads in [1, 4, 27] {
+        let pool = rayon::ThreadPoolBuilder::new()
+            .num_threads(threads)
+            .build()
+            .unwrap();
+        for n in [100_000, 1_000_000, 4_000_000] {
+            let baseline = CURRENT.load(Ordering::Relaxed);
+            PEAK.store(baseline, Ordering::Relaxed);
+            let value = pool.install(|| Fib::single(n));
+            let peak = (PEAK.load(Ordering::Relaxed) - baseline) as u64;
+
+            // Sequentially, peak memory stays a small constant multiple of the result, and
+            // parallel multiplication stays within the estimate for its pool
+            let output = value.bits().div_ceil(8);
+            if threads == 1 {
+                assert!(peak <= output * 7, "F({n}): peak {peak} bytes");
+            }
+            assert!(
+                pool.install(|| Fib::estimate_single(n)).peak_bytes >= peak,
+                "F({n}) on {threads} threads: peak {peak} bytes"
+            );
+        }
+    }
+}